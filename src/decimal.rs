@@ -0,0 +1,580 @@
+use serde::de::{Error as DeError, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+/// Maximum number of fractional digits kept by arithmetic
+pub const MAX_SCALE: u32 = 28;
+
+/// Exact decimal number stored as `mantissa * 10^-scale`.
+///
+/// Prices and sizes travel as strings on the wire ("2000.0", "0.0031"). Parsing keeps the
+/// scale, so serializing gives back exactly the string that was received. Equality and
+/// ordering compare the numeric value, so `2000.0 == 2000`. Use [`Decimal::normalize`] for
+/// the canonical form expected when hashing actions.
+///
+/// # Panics
+///
+/// Like integer arithmetic, `+`, `-`, `*` and [`Sum`] panic if the result does not fit the
+/// mantissa, e.g. for very long mantissas or scales far apart. Use the `checked_*` methods on
+/// values that come from the wire.
+#[derive(Clone, Copy, Default)]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Toward negative infinity
+    Down,
+    /// Toward positive infinity
+    Up,
+    /// To the nearest value, ties away from zero
+    Nearest,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDecimalError(String);
+
+impl Display for ParseDecimalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid decimal: {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseDecimalError {}
+
+fn pow10(exp: u32) -> Option<i128> {
+    10i128.checked_pow(exp)
+}
+
+impl Decimal {
+    pub const ZERO: Decimal = Decimal {
+        mantissa: 0,
+        scale: 0,
+    };
+    pub const ONE: Decimal = Decimal {
+        mantissa: 1,
+        scale: 0,
+    };
+
+    /// `mantissa * 10^-scale`
+    pub const fn new(mantissa: i128, scale: u32) -> Self {
+        Self { mantissa, scale }
+    }
+    pub const fn mantissa(&self) -> i128 {
+        self.mantissa
+    }
+    /// Number of fractional digits
+    pub const fn scale(&self) -> u32 {
+        self.scale
+    }
+    pub const fn is_zero(&self) -> bool {
+        self.mantissa == 0
+    }
+    pub const fn is_sign_negative(&self) -> bool {
+        self.mantissa < 0
+    }
+    pub const fn is_sign_positive(&self) -> bool {
+        self.mantissa > 0
+    }
+    pub const fn abs(&self) -> Self {
+        Self {
+            mantissa: self.mantissa.abs(),
+            scale: self.scale,
+        }
+    }
+
+    /// Drop trailing fractional zeros: `2000.0` becomes `2000`, `0.50` becomes `0.5`
+    pub fn normalize(&self) -> Self {
+        let mut mantissa = self.mantissa;
+        let mut scale = self.scale;
+        if mantissa == 0 {
+            return Self::ZERO;
+        }
+        while scale > 0 && mantissa % 10 == 0 {
+            mantissa /= 10;
+            scale -= 1;
+        }
+        Self { mantissa, scale }
+    }
+
+    /// The same value expressed with `scale` fractional digits. `None` if that would lose
+    /// precision or overflow
+    pub fn with_scale(&self, scale: u32) -> Option<Self> {
+        if scale >= self.scale {
+            let mantissa = self.mantissa.checked_mul(pow10(scale - self.scale)?)?;
+            Some(Self { mantissa, scale })
+        } else {
+            let factor = pow10(self.scale - scale)?;
+            (self.mantissa % factor == 0).then(|| Self {
+                mantissa: self.mantissa / factor,
+                scale,
+            })
+        }
+    }
+
+    /// Bring both operands to a common scale
+    fn align(a: &Self, b: &Self) -> Option<(i128, i128, u32)> {
+        let scale = a.scale.max(b.scale);
+        Some((
+            a.with_scale(scale)?.mantissa,
+            b.with_scale(scale)?.mantissa,
+            scale,
+        ))
+    }
+
+    /// Cut fractional digits beyond `scale`, rounding toward zero
+    pub fn trunc_to(&self, scale: u32) -> Self {
+        if scale >= self.scale {
+            return *self;
+        }
+        match pow10(self.scale - scale) {
+            Some(factor) => Self {
+                mantissa: self.mantissa / factor,
+                scale,
+            },
+            None => Self::ZERO,
+        }
+    }
+
+    /// Round to `scale` fractional digits
+    pub fn round_to(&self, scale: u32, mode: RoundingMode) -> Self {
+        if scale >= self.scale {
+            return *self;
+        }
+        let Some(factor) = pow10(self.scale - scale) else {
+            return Self::ZERO;
+        };
+        let quotient = self.mantissa / factor;
+        let remainder = self.mantissa % factor;
+        let adjust = if remainder == 0 {
+            0
+        } else {
+            match mode {
+                RoundingMode::Down => -((remainder < 0) as i128),
+                RoundingMode::Up => (remainder > 0) as i128,
+                RoundingMode::Nearest => {
+                    if remainder.unsigned_abs() * 2 >= factor.unsigned_abs() {
+                        remainder.signum()
+                    } else {
+                        0
+                    }
+                }
+            }
+        };
+        Self {
+            mantissa: quotient + adjust,
+            scale,
+        }
+    }
+
+    /// Number of fractional digits needed to keep `sig_figs` significant figures
+    pub fn significant_decimals(&self, sig_figs: u32) -> u32 {
+        if self.mantissa == 0 {
+            return 0;
+        }
+        let digits = self.mantissa.unsigned_abs().ilog10() as i64 + 1;
+        // power of ten of the leading digit
+        let exponent = digits - self.scale as i64 - 1;
+        (sig_figs as i64 - 1 - exponent).max(0) as u32
+    }
+
+    /// `None` on overflow, also when trailing zeros are dropped first
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        let (a, b, scale) = Self::align(&self, &rhs)
+            .or_else(|| Self::align(&self.normalize(), &rhs.normalize()))?;
+        Some(Self {
+            mantissa: a.checked_add(b)?,
+            scale,
+        })
+    }
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.checked_add(-rhs)
+    }
+    /// Product with up to [`MAX_SCALE`] fractional digits. `None` on overflow, also when
+    /// trailing zeros are dropped first
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        let exact = |a: Self, b: Self| {
+            Some(Self {
+                mantissa: a.mantissa.checked_mul(b.mantissa)?,
+                scale: a.scale.checked_add(b.scale)?,
+            })
+        };
+        let product = exact(self, rhs).or_else(|| exact(self.normalize(), rhs.normalize()))?;
+        if product.scale > MAX_SCALE {
+            Some(product.normalize().trunc_to(MAX_SCALE))
+        } else {
+            Some(product)
+        }
+    }
+    /// Quotient with up to [`MAX_SCALE`] fractional digits, truncated toward zero.
+    /// `None` on division by zero or overflow
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        if rhs.is_zero() {
+            return None;
+        }
+        // the widest scale whose numerator still fits in i128
+        let mut scale = MAX_SCALE;
+        loop {
+            // self / rhs = (m1 * 10^(scale + s2 - s1) / m2) * 10^-scale
+            let shift = (scale + rhs.scale).checked_sub(self.scale);
+            let numerator = match shift {
+                Some(shift) => pow10(shift).and_then(|p| self.mantissa.checked_mul(p)),
+                None => pow10(self.scale - scale - rhs.scale).map(|p| self.mantissa / p),
+            };
+            if let Some(numerator) = numerator {
+                return Some(
+                    Self {
+                        mantissa: numerator / rhs.mantissa,
+                        scale,
+                    }
+                    .normalize(),
+                );
+            }
+            scale = scale.checked_sub(1)?;
+        }
+    }
+
+    /// Nearest `f64`, for analytics where exactness does not matter
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match Self::align(self, other) {
+            Some((a, b, _)) => a.cmp(&b),
+            // only reachable for values near the i128 limits
+            None => self.to_f64().total_cmp(&other.to_f64()),
+        }
+    }
+}
+impl Hash for Decimal {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let normalized = self.normalize();
+        normalized.mantissa.hash(state);
+        normalized.scale.hash(state);
+    }
+}
+
+impl Neg for Decimal {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            mantissa: -self.mantissa,
+            scale: self.scale,
+        }
+    }
+}
+impl Add for Decimal {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        self.checked_add(rhs).expect("decimal overflow")
+    }
+}
+impl Sub for Decimal {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self.checked_sub(rhs).expect("decimal overflow")
+    }
+}
+impl Mul for Decimal {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        self.checked_mul(rhs).expect("decimal overflow")
+    }
+}
+impl AddAssign for Decimal {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+impl SubAssign for Decimal {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+impl Sum for Decimal {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, Add::add)
+    }
+}
+impl<'a> Sum<&'a Decimal> for Decimal {
+    fn sum<I: Iterator<Item = &'a Decimal>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(value: i64) -> Self {
+        Self::new(value as i128, 0)
+    }
+}
+impl From<u64> for Decimal {
+    fn from(value: u64) -> Self {
+        Self::new(value as i128, 0)
+    }
+}
+impl From<u32> for Decimal {
+    fn from(value: u32) -> Self {
+        Self::new(value as i128, 0)
+    }
+}
+impl TryFrom<f64> for Decimal {
+    type Error = ParseDecimalError;
+
+    /// Uses the shortest representation that round-trips, so `0.1` becomes exactly `0.1`
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        if !value.is_finite() {
+            return Err(ParseDecimalError(value.to_string()));
+        }
+        value.to_string().parse()
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = ParseDecimalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseDecimalError(s.to_string());
+        let (number, exponent) = match s.find(['e', 'E']) {
+            Some(i) => (&s[..i], s[i + 1..].parse::<i32>().map_err(|_| err())?),
+            None => (s, 0),
+        };
+        let (negative, digits) = match number.as_bytes().first() {
+            Some(b'-') => (true, &number[1..]),
+            Some(b'+') => (false, &number[1..]),
+            _ => (false, number),
+        };
+        let (int_part, frac_part) = digits.split_once('.').unwrap_or((digits, ""));
+        if int_part.is_empty() && frac_part.is_empty() {
+            return Err(err());
+        }
+        let mut mantissa: i128 = 0;
+        for c in int_part.chars().chain(frac_part.chars()) {
+            let digit = c.to_digit(10).ok_or_else(err)?;
+            mantissa = mantissa
+                .checked_mul(10)
+                .and_then(|m| m.checked_add(digit as i128))
+                .ok_or_else(err)?;
+        }
+        let mut scale = frac_part.len() as i64 - exponent as i64;
+        if scale < 0 {
+            mantissa = pow10(-scale as u32)
+                .and_then(|p| mantissa.checked_mul(p))
+                .ok_or_else(err)?;
+            scale = 0;
+        }
+        if scale > u32::MAX as i64 {
+            return Err(err());
+        }
+        Ok(Self {
+            mantissa: if negative { -mantissa } else { mantissa },
+            scale: scale as u32,
+        })
+    }
+}
+
+impl Display for Decimal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let digits = self.mantissa.unsigned_abs().to_string();
+        if self.mantissa < 0 {
+            f.write_str("-")?;
+        }
+        let scale = self.scale as usize;
+        if scale == 0 {
+            return f.write_str(&digits);
+        }
+        if digits.len() > scale {
+            let (int_part, frac_part) = digits.split_at(digits.len() - scale);
+            write!(f, "{int_part}.{frac_part}")
+        } else {
+            write!(f, "0.{:0>scale$}", digits)
+        }
+    }
+}
+impl Debug for Decimal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl Serialize for Decimal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Decimal {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DecimalVisitor;
+
+        impl Visitor<'_> for DecimalVisitor {
+            type Value = Decimal;
+
+            fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+                f.write_str("a decimal number or string")
+            }
+            fn visit_str<E: DeError>(self, v: &str) -> Result<Decimal, E> {
+                v.parse().map_err(E::custom)
+            }
+            fn visit_i64<E: DeError>(self, v: i64) -> Result<Decimal, E> {
+                Ok(v.into())
+            }
+            fn visit_u64<E: DeError>(self, v: u64) -> Result<Decimal, E> {
+                Ok(v.into())
+            }
+            fn visit_f64<E: DeError>(self, v: f64) -> Result<Decimal, E> {
+                Decimal::try_from(v).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(DecimalVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display_round_trip() {
+        for s in [
+            "0",
+            "2000.0",
+            "3.5",
+            "0.0031",
+            "-12.340",
+            "0.00000001",
+            "65000",
+            "1.000000",
+        ] {
+            assert_eq!(d(s).to_string(), s);
+        }
+        assert_eq!(d("+1.5").to_string(), "1.5");
+        assert_eq!(d(".5").to_string(), "0.5");
+        assert_eq!(d("1e-5").to_string(), "0.00001");
+        assert_eq!(d("1.5E3").to_string(), "1500");
+        for s in ["", "-", ".", "abc", "1.2.3", "1e", "--1"] {
+            assert!(s.parse::<Decimal>().is_err(), "{s}");
+        }
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(d("2000.0").normalize().to_string(), "2000");
+        assert_eq!(d("0.50").normalize().to_string(), "0.5");
+        assert_eq!(d("-0.000").normalize().to_string(), "0");
+        assert_eq!(d("100").normalize().to_string(), "100");
+    }
+
+    #[test]
+    fn test_compare_by_value() {
+        assert_eq!(d("2000.0"), d("2000"));
+        assert!(d("0.1") < d("0.10001"));
+        assert!(d("-1") < d("0"));
+        assert_eq!(d("1.50").max(d("1.5")), d("1.5"));
+
+        let mut set = std::collections::HashSet::new();
+        set.insert(d("1.0"));
+        assert!(set.contains(&d("1")));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(d("0.1") + d("0.2"), d("0.3"));
+        assert_eq!(d("1") - d("0.0031"), d("0.9969"));
+        assert_eq!(d("2700") * d("0.0031"), d("8.37"));
+        assert_eq!(-d("1.5"), d("-1.5"));
+        assert_eq!(
+            d("1").checked_div(d("3")).unwrap().to_string(),
+            "0.3333333333333333333333333333"
+        );
+        assert_eq!(d("10").checked_div(d("4")).unwrap(), d("2.5"));
+        assert_eq!(d("1").checked_div(Decimal::ZERO), None);
+        assert_eq!([d("1.5"), d("2.25")].iter().sum::<Decimal>(), d("3.75"));
+        assert_eq!(d("1.239").trunc_to(2), d("1.23"));
+        assert_eq!(d("-1.239").trunc_to(2), d("-1.23"));
+        assert_eq!(d("1.20").with_scale(1), Some(d("1.2")));
+        assert_eq!(d("1.25").with_scale(1), None);
+    }
+
+    #[test]
+    fn test_checked_overflow() {
+        let max = Decimal::new(i128::MAX, 0);
+        assert_eq!(max.checked_add(Decimal::ONE), None);
+        assert_eq!(max.checked_mul(d("2")), None);
+        assert_eq!((-max).checked_sub(d("2")), None);
+        // scales too far apart to align
+        assert_eq!(d("1e30").checked_add(Decimal::new(1, 20)), None);
+
+        // trailing zeros from the wire do not overflow
+        let padded = Decimal::new(2 * 10i128.pow(36), 36);
+        assert_eq!(padded.checked_mul(padded), Some(d("4")));
+        assert_eq!(padded.checked_add(d("1e10")), Some(d("10000000002")));
+        assert_eq!(
+            Decimal::new(1, u32::MAX).checked_mul(Decimal::new(1, 1)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rounding() {
+        use RoundingMode::*;
+        assert_eq!(d("1.234").round_to(2, Down), d("1.23"));
+        assert_eq!(d("1.234").round_to(2, Up), d("1.24"));
+        assert_eq!(d("1.235").round_to(2, Nearest), d("1.24"));
+        assert_eq!(d("1.2349").round_to(2, Nearest), d("1.23"));
+        assert_eq!(d("-1.234").round_to(2, Down), d("-1.24"));
+        assert_eq!(d("-1.234").round_to(2, Up), d("-1.23"));
+        assert_eq!(d("-1.235").round_to(2, Nearest), d("-1.24"));
+        assert_eq!(d("1.2").round_to(4, Up).to_string(), "1.2");
+        assert_eq!(d("9.99").round_to(0, Up), d("10"));
+
+        assert_eq!(d("12345.6").significant_decimals(5), 0);
+        assert_eq!(d("1234.56").significant_decimals(5), 1);
+        assert_eq!(d("0.0012345").significant_decimals(5), 7);
+        assert_eq!(d("1.0").significant_decimals(5), 4);
+        assert_eq!(Decimal::ZERO.significant_decimals(5), 0);
+    }
+
+    #[test]
+    fn test_serde() -> eyre::Result<()> {
+        let values: Vec<Decimal> = serde_json::from_str(r#"["2000.0", 3, 0.1, "-0.5"]"#)?;
+        assert_eq!(values, vec![d("2000"), d("3"), d("0.1"), d("-0.5")]);
+        assert_eq!(
+            serde_json::to_string(&values)?,
+            r#"["2000.0","3","0.1","-0.5"]"#
+        );
+        assert_eq!(Decimal::try_from(0.1)?, d("0.1"));
+        assert!(serde_json::from_str::<Decimal>(r#""x""#).is_err());
+        Ok(())
+    }
+}
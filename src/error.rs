@@ -1,9 +1,10 @@
-use crate::execution::models::Subscription as HyperliquidSubscription;
+use crate::market::models::Subscription as HyperliquidSubscription;
 use std::time::SystemTimeError;
 use thiserror::Error as ThisError;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(ThisError, Debug)]
 pub enum Error {
     #[error("Reqwest error: {0:?}")]
@@ -13,18 +14,37 @@ pub enum Error {
     #[error("Wallet error: {0:?}")]
     WalletError(alloy::signers::Error),
 
+    #[error("WebSocket error: {0:?}")]
+    WebSocket(Box<tokio_tungstenite::tungstenite::Error>),
+
     #[error("Not connected")]
     NotConnected,
     #[error("JSON error: {0:?}")]
     Json(serde_json::Error),
+    #[error("MessagePack error: {0:?}")]
+    MsgPack(rmp_serde::encode::Error),
     #[error("Not subscribed to channel with id {0}")]
     NotSubscribed(u64),
     #[error("Subscription failed: {0:?}")]
     SubscriptionFailed(HyperliquidSubscription),
     #[error("Missing subscription response: {0:?}")]
     MissingSubscriptionResponse(HyperliquidSubscription),
-    #[error("Response error: {0}")]
-    ResponseError(String),
+    #[error("Response error {status}: {body}")]
+    ResponseError { status: u16, body: String },
+    #[error("Exchange error: {0}")]
+    ExchangeError(String),
+    #[error("Invalid order: {0}")]
+    InvalidOrder(String),
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+    #[error("Unknown asset: {0}")]
+    UnknownAsset(String),
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+    #[error("Invalid book snapshot: {0}")]
+    InvalidSnapshot(String),
+    #[error("Decimal overflow: {0}")]
+    DecimalOverflow(String),
 }
 
 impl From<reqwest::Error> for Error {
@@ -45,14 +65,28 @@ impl From<alloy::signers::Error> for Error {
     }
 }
 
+impl From<tokio_tungstenite::tungstenite::Error> for Error {
+    fn from(e: tokio_tungstenite::tungstenite::Error) -> Self {
+        Self::WebSocket(Box::new(e))
+    }
+}
+
 impl From<serde_json::Error> for Error {
     fn from(e: serde_json::Error) -> Self {
         Self::Json(e)
     }
 }
+impl From<rmp_serde::encode::Error> for Error {
+    fn from(e: rmp_serde::encode::Error) -> Self {
+        Self::MsgPack(e)
+    }
+}
 impl Error {
-    pub fn response_error(msg: impl Into<String>) -> Self {
-        Self::ResponseError(msg.into())
+    pub fn response_error(status: u16, body: impl Into<String>) -> Self {
+        Self::ResponseError {
+            status,
+            body: body.into(),
+        }
     }
 }
 
@@ -0,0 +1,362 @@
+use crate::Decimal;
+use crate::error::{Error, Result};
+use crate::execution::models::{Grouping, HyperliquidOrderRequest, HyperliquidOrderType, TpSl};
+
+/// A take-profit or stop-loss leg. Market legs still need `limit_px` as the worst fill price
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TriggerLeg {
+    pub trigger_px: Decimal,
+    pub limit_px: Decimal,
+    pub is_market: bool,
+}
+
+impl TriggerLeg {
+    pub fn market(trigger_px: Decimal, limit_px: Decimal) -> Self {
+        Self {
+            trigger_px,
+            limit_px,
+            is_market: true,
+        }
+    }
+    pub fn limit(trigger_px: Decimal, limit_px: Decimal) -> Self {
+        Self {
+            trigger_px,
+            limit_px,
+            is_market: false,
+        }
+    }
+}
+
+/// Orders and grouping ready for [`crate::execution::models::Action::Order`]
+#[derive(Debug, Clone)]
+pub struct BracketOrder {
+    pub orders: Vec<HyperliquidOrderRequest>,
+    pub grouping: Grouping,
+}
+
+/// Builds an entry order with linked take-profit and stop-loss children (`normalTpsl`), or
+/// take-profit and stop-loss orders on an existing position (`positionTpsl`).
+///
+/// Children close the entry or position: they are on the opposite side, reduce-only and of
+/// the same size.
+#[derive(Debug, Clone)]
+pub struct BracketOrderBuilder {
+    entry: Option<HyperliquidOrderRequest>,
+    asset: u32,
+    /// side of the children
+    is_buy: bool,
+    sz: Decimal,
+    take_profit: Option<TriggerLeg>,
+    stop_loss: Option<TriggerLeg>,
+}
+
+impl BracketOrderBuilder {
+    /// Bracket around a new entry order
+    pub fn new(entry: HyperliquidOrderRequest) -> Self {
+        Self {
+            asset: entry.asset,
+            is_buy: !entry.is_buy,
+            sz: entry.sz,
+            entry: Some(entry),
+            take_profit: None,
+            stop_loss: None,
+        }
+    }
+    /// Take-profit and stop-loss for an open position of `sz` on `asset`
+    pub fn for_position(asset: u32, is_long: bool, sz: Decimal) -> Self {
+        Self {
+            entry: None,
+            asset,
+            is_buy: !is_long,
+            sz,
+            take_profit: None,
+            stop_loss: None,
+        }
+    }
+    pub fn take_profit(mut self, leg: TriggerLeg) -> Self {
+        self.take_profit = Some(leg);
+        self
+    }
+    pub fn stop_loss(mut self, leg: TriggerLeg) -> Self {
+        self.stop_loss = Some(leg);
+        self
+    }
+
+    pub fn build(self) -> Result<BracketOrder> {
+        if self.take_profit.is_none() && self.stop_loss.is_none() {
+            return Err(Error::InvalidOrder(
+                "bracket needs a take-profit or a stop-loss".to_string(),
+            ));
+        }
+        let grouping = match self.entry {
+            Some(_) => Grouping::NormalTpsl,
+            None => Grouping::PositionTpsl,
+        };
+        let mut orders = vec![];
+        if let Some(entry) = self.entry.clone() {
+            if matches!(entry.order_type, HyperliquidOrderType::Trigger { .. }) {
+                return Err(Error::InvalidOrder(
+                    "bracket entry must be a limit order".to_string(),
+                ));
+            }
+            if entry.reduce_only {
+                return Err(Error::InvalidOrder(
+                    "bracket entry must not be reduce-only".to_string(),
+                ));
+            }
+            orders.push(entry);
+        }
+        let legs = [(TpSl::Tp, self.take_profit), (TpSl::Sl, self.stop_loss)];
+        for (tpsl, leg) in legs {
+            if let Some(leg) = leg {
+                orders.push(self.child(tpsl, leg));
+            }
+        }
+        let bracket = BracketOrder { orders, grouping };
+        validate_bracket(&bracket.orders, grouping)?;
+        Ok(bracket)
+    }
+
+    fn child(&self, tpsl: TpSl, leg: TriggerLeg) -> HyperliquidOrderRequest {
+        HyperliquidOrderRequest {
+            asset: self.asset,
+            is_buy: self.is_buy,
+            limit_px: leg.limit_px,
+            sz: self.sz,
+            reduce_only: true,
+            order_type: HyperliquidOrderType::Trigger {
+                is_market: leg.is_market,
+                trigger_px: leg.trigger_px,
+                tpsl,
+            },
+            cloid: None,
+        }
+    }
+}
+
+/// Check that a batch of orders is consistent with its TP/SL grouping: children are
+/// reduce-only triggers on the same asset, closing the entry (`normalTpsl`) or each other's
+/// side (`positionTpsl`), with at most one take-profit and one stop-loss whose trigger prices
+/// sit on the correct side of the entry price.
+pub fn validate_bracket(orders: &[HyperliquidOrderRequest], grouping: Grouping) -> Result<()> {
+    let invalid = |msg: &str| Err(Error::InvalidOrder(msg.to_string()));
+    let (entry, children) = match grouping {
+        Grouping::Na => return Ok(()),
+        Grouping::NormalTpsl => match orders.split_first() {
+            Some((entry, children)) => (Some(entry), children),
+            None => return invalid("normalTpsl needs an entry order"),
+        },
+        Grouping::PositionTpsl => (None, orders),
+    };
+    let Some(first) = children.first() else {
+        return invalid("no take-profit or stop-loss orders");
+    };
+    // side of the children; closing a long means selling
+    let is_buy = entry.map_or(first.is_buy, |entry| !entry.is_buy);
+    let asset = entry.map_or(first.asset, |entry| entry.asset);
+
+    let (mut tp, mut sl) = (0, 0);
+    for child in children {
+        let HyperliquidOrderType::Trigger {
+            trigger_px, tpsl, ..
+        } = &child.order_type
+        else {
+            return invalid("take-profit and stop-loss orders must be triggers");
+        };
+        if child.asset != asset {
+            return invalid("take-profit and stop-loss must be on the entry asset");
+        }
+        if child.is_buy != is_buy {
+            return invalid("take-profit and stop-loss must close the entry side");
+        }
+        if !child.reduce_only {
+            return invalid("take-profit and stop-loss must be reduce-only");
+        }
+        if let Some(entry) = entry {
+            if child.sz != entry.sz {
+                return invalid("take-profit and stop-loss must match the entry size");
+            }
+            // a take-profit on a long triggers above the entry, a stop-loss below
+            let above_entry = *trigger_px > entry.limit_px;
+            let expect_above = (*tpsl == TpSl::Tp) == entry.is_buy;
+            if above_entry != expect_above || *trigger_px == entry.limit_px {
+                return invalid("trigger price is on the wrong side of the entry price");
+            }
+        }
+        match tpsl {
+            TpSl::Tp => tp += 1,
+            TpSl::Sl => sl += 1,
+        }
+    }
+    if tp > 1 || sl > 1 {
+        return invalid("at most one take-profit and one stop-loss");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::models::{Action, HyperliquidChain, HyperliquidTif};
+    use crate::rest::exchange::tests::{packb, sdk_order};
+    use crate::sign::sign_l1_action;
+    use crate::sign::tests::reference_wallet;
+    use alloy::primitives::Address;
+    use malachite::base::strings::ToLowerHexString;
+
+    fn d(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    fn entry(is_buy: bool) -> HyperliquidOrderRequest {
+        HyperliquidOrderRequest {
+            asset: 1,
+            is_buy,
+            limit_px: d("2700"),
+            sz: d("0.01"),
+            reduce_only: false,
+            order_type: HyperliquidOrderType::Limit {
+                tif: HyperliquidTif::Gtc,
+            },
+            cloid: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_trigger_signing_matches_python_sdk() -> eyre::Result<()> {
+        // `test_l1_action_signing_tpsl_order_matches` in the Python SDK's tests/signing_test.py
+        let wallet = reference_wallet();
+        let action = Action::Order {
+            orders: vec![sdk_order(HyperliquidOrderType::Trigger {
+                is_market: true,
+                trigger_px: d("103"),
+                tpsl: TpSl::Sl,
+            })],
+            grouping: Grouping::Na,
+        };
+        let connection_id = action.hash(0, Address::ZERO)?;
+
+        let mainnet = sign_l1_action(HyperliquidChain::Arbitrum, &wallet, connection_id).await?;
+        assert_eq!(
+            mainnet.r.to_lower_hex_string(),
+            "98343f2b5ae8e26bb2587daad3863bc70d8792b09af1841b6fdd530a2065a3f9"
+        );
+        assert_eq!(
+            mainnet.s.to_lower_hex_string(),
+            "6b5bb6bb0633b710aa22b721dd9dee6d083646a5f8e581a20b545be6c1feb405"
+        );
+        assert_eq!(mainnet.v, 27);
+
+        let testnet =
+            sign_l1_action(HyperliquidChain::ArbitrumGoerli, &wallet, connection_id).await?;
+        assert_eq!(
+            testnet.r.to_lower_hex_string(),
+            "971c554d917c44e0e1b6cc45d8f9404f32172a9d3b3566262347d0302896a2e4"
+        );
+        assert_eq!(
+            testnet.s.to_lower_hex_string(),
+            "206257b104788f80450f8e786c329daa589aa0b32ba96948201ae556d5637eac"
+        );
+        Ok(())
+    }
+
+    /// `{"type": "order", "orders": order_wires, "grouping": grouping}` as built by
+    /// `order_wires_to_order_action` in the Python SDK, from wires whose encoding the SDK
+    /// signing vectors cover
+    fn sdk_order_action(
+        orders: &[HyperliquidOrderRequest],
+        grouping: &str,
+    ) -> eyre::Result<Vec<u8>> {
+        let mut wires = vec![0x90 | orders.len() as u8];
+        for order in orders {
+            wires.extend(rmp_serde::to_vec_named(order)?);
+        }
+        Ok(packb(&[
+            ("type", rmp_serde::to_vec("order")?),
+            ("orders", wires),
+            ("grouping", rmp_serde::to_vec(grouping)?),
+        ]))
+    }
+
+    #[test]
+    fn test_normal_tpsl_wire() -> eyre::Result<()> {
+        let bracket = BracketOrderBuilder::new(entry(true))
+            .take_profit(TriggerLeg::limit(d("2900"), d("2900")))
+            .stop_loss(TriggerLeg::market(d("2600"), d("2500")))
+            .build()?;
+        assert_eq!(bracket.grouping, Grouping::NormalTpsl);
+        let expected = sdk_order_action(&bracket.orders, "normalTpsl")?;
+        let action = Action::Order {
+            orders: bracket.orders,
+            grouping: bracket.grouping,
+        };
+        assert_eq!(
+            serde_json::to_string(&action)?,
+            r#"{"type":"order","orders":[{"a":1,"b":true,"p":"2700","s":"0.01","r":false,"t":{"limit":{"tif":"Gtc"}}},{"a":1,"b":false,"p":"2900","s":"0.01","r":true,"t":{"trigger":{"isMarket":false,"triggerPx":"2900","tpsl":"tp"}}},{"a":1,"b":false,"p":"2500","s":"0.01","r":true,"t":{"trigger":{"isMarket":true,"triggerPx":"2600","tpsl":"sl"}}}],"grouping":"normalTpsl"}"#
+        );
+        assert_eq!(rmp_serde::to_vec_named(&action)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_position_tpsl_wire() -> eyre::Result<()> {
+        let bracket = BracketOrderBuilder::for_position(1, false, d("0.5"))
+            .stop_loss(TriggerLeg::market(d("2800"), d("2900")))
+            .build()?;
+        assert_eq!(bracket.grouping, Grouping::PositionTpsl);
+        assert_eq!(bracket.orders.len(), 1);
+        assert!(bracket.orders[0].is_buy);
+        let expected = sdk_order_action(&bracket.orders, "positionTpsl")?;
+        let action = Action::Order {
+            orders: bracket.orders,
+            grouping: bracket.grouping,
+        };
+        assert_eq!(
+            serde_json::to_string(&action)?,
+            r#"{"type":"order","orders":[{"a":1,"b":true,"p":"2900","s":"0.5","r":true,"t":{"trigger":{"isMarket":true,"triggerPx":"2800","tpsl":"sl"}}}],"grouping":"positionTpsl"}"#
+        );
+        assert_eq!(rmp_serde::to_vec_named(&action)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_validation() {
+        // stop-loss above a long entry
+        let result = BracketOrderBuilder::new(entry(true))
+            .stop_loss(TriggerLeg::market(d("2800"), d("2700")))
+            .build();
+        assert!(matches!(result, Err(Error::InvalidOrder(_))), "{result:?}");
+        // short entry: take-profit below, stop-loss above
+        let short = BracketOrderBuilder::new(entry(false))
+            .take_profit(TriggerLeg::market(d("2500"), d("2550")))
+            .stop_loss(TriggerLeg::market(d("2800"), d("2850")))
+            .build()
+            .unwrap();
+        assert!(short.orders[1..].iter().all(|o| o.is_buy && o.reduce_only));
+        assert!(BracketOrderBuilder::new(entry(true)).build().is_err());
+
+        let reduce_only_entry = HyperliquidOrderRequest {
+            reduce_only: true,
+            ..entry(true)
+        };
+        let result = BracketOrderBuilder::new(reduce_only_entry)
+            .take_profit(TriggerLeg::market(d("2900"), d("2900")))
+            .build();
+        assert!(result.is_err());
+
+        // hand-assembled batches
+        let mut orders = short.orders.clone();
+        orders[1].reduce_only = false;
+        assert!(validate_bracket(&orders, Grouping::NormalTpsl).is_err());
+        let mut orders = short.orders.clone();
+        orders[2].is_buy = false;
+        assert!(validate_bracket(&orders, Grouping::NormalTpsl).is_err());
+        let mut orders = short.orders.clone();
+        orders.push(orders[2].clone());
+        assert!(validate_bracket(&orders, Grouping::NormalTpsl).is_err());
+        assert!(validate_bracket(&short.orders, Grouping::NormalTpsl).is_ok());
+        assert!(validate_bracket(&short.orders[1..], Grouping::PositionTpsl).is_ok());
+        assert!(validate_bracket(&short.orders, Grouping::PositionTpsl).is_err());
+        assert!(validate_bracket(&short.orders, Grouping::Na).is_ok());
+    }
+}
@@ -1 +1,6 @@
+pub mod bracket;
 pub mod models;
+pub mod nonce;
+pub mod order_manager;
+pub mod precision;
+pub mod ws;
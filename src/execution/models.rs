@@ -1,20 +1,13 @@
 // Execution-related model definitions
+use crate::Decimal;
+use crate::error::{Error, Result};
+use crate::sign::UserSignedAction;
 use alloy::primitives::{Address, B256, keccak256};
-use eyre::Result;
+use alloy::sol_types::SolValue;
 use serde::{Deserialize, Serialize};
 
 // Re-export from the original agent module
 pub mod agent {
-    use alloy::primitives::B256;
-    use serde::Serialize;
-
-    #[derive(Serialize, Debug)]
-    #[serde(rename_all = "camelCase")]
-    pub struct Agent {
-        pub source: String,
-        pub connection_id: B256,
-    }
-
     pub mod agent_sol {
         use alloy::core::sol;
 
@@ -36,6 +29,16 @@ pub enum HyperliquidChain {
     ArbitrumGoerli,
 }
 
+impl HyperliquidChain {
+    /// `hyperliquidChain` field of user-signed actions
+    pub fn hyperliquid_chain(&self) -> &'static str {
+        match self {
+            HyperliquidChain::Arbitrum => "Mainnet",
+            HyperliquidChain::Dev | HyperliquidChain::ArbitrumGoerli => "Testnet",
+        }
+    }
+}
+
 // Order and execution types
 #[derive(Serialize, Debug, Clone)]
 pub enum HyperliquidTif {
@@ -45,7 +48,7 @@ pub enum HyperliquidTif {
     FrontendMarket = 8,
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum TpSl {
     Tp,
@@ -61,7 +64,8 @@ pub enum HyperliquidOrderType {
     #[serde(rename_all = "camelCase")]
     Trigger {
         is_market: bool,
-        trigger_px: String,
+        #[serde(serialize_with = "serialize_wire_decimal")]
+        trigger_px: Decimal,
         tpsl: TpSl,
     },
 }
@@ -73,10 +77,14 @@ pub struct HyperliquidOrderRequest {
     pub asset: u32,
     #[serde(rename = "b", alias = "isBuy")]
     pub is_buy: bool,
-    #[serde(rename = "p", alias = "limitPx")]
-    pub limit_px: String,
-    #[serde(rename = "s", alias = "sz")]
-    pub sz: String,
+    #[serde(
+        rename = "p",
+        alias = "limitPx",
+        serialize_with = "serialize_wire_decimal"
+    )]
+    pub limit_px: Decimal,
+    #[serde(rename = "s", alias = "sz", serialize_with = "serialize_wire_decimal")]
+    pub sz: Decimal,
     #[serde(rename = "r", alias = "reduceOnly", default)]
     pub reduce_only: bool,
     #[serde(rename = "t", alias = "orderType")]
@@ -85,16 +93,22 @@ pub struct HyperliquidOrderRequest {
     pub cloid: Option<String>,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub enum Grouping {
     Na,
+    /// Entry order followed by take-profit and/or stop-loss children sized to the entry
+    NormalTpsl,
+    /// Take-profit and/or stop-loss attached to the whole position
+    PositionTpsl,
 }
 
 impl Grouping {
     pub fn to_i32(&self) -> i32 {
         match self {
             Grouping::Na => 0,
+            Grouping::NormalTpsl => 1,
+            Grouping::PositionTpsl => 2,
         }
     }
 }
@@ -115,25 +129,283 @@ pub struct RequestCancelByClientId {
     pub cloid: String,
 }
 
-#[derive(Serialize, Debug)]
+/// Identifies a resting order either by exchange order id or by client order id
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum OrderId {
+    Oid(u64),
+    Cloid(String),
+}
+
+impl From<u64> for OrderId {
+    fn from(oid: u64) -> Self {
+        Self::Oid(oid)
+    }
+}
+
+/// Replace a resting order with `order`, keeping its queue position where the exchange allows
+#[derive(Serialize, Debug, Clone)]
+pub struct ModifyRequest {
+    pub oid: OrderId,
+    pub order: HyperliquidOrderRequest,
+}
+
+/// `signatureChainId` used by the official SDKs for user-signed actions (Arbitrum Sepolia)
+pub const DEFAULT_SIGNATURE_CHAIN_ID: u64 = 0x66eee;
+
+fn serialize_chain_id<S: serde::Serializer>(
+    chain_id: &u64,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_str(&format_args!("{chain_id:#x}"))
+}
+
+/// Addresses inside L1 actions are hashed as lowercase hex strings, also by msgpack
+fn serialize_address<S: serde::Serializer>(
+    address: &Address,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_str(&format_args!("{address:#x}"))
+}
+
+/// Prices and sizes are hashed without trailing zeros, like `float_to_wire` in the official SDK
+fn serialize_wire_decimal<S: serde::Serializer>(
+    value: &Decimal,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    value.normalize().serialize(serializer)
+}
+
+/// Transfer USDC to another address on Hyperliquid
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UsdSend {
+    #[serde(serialize_with = "serialize_chain_id")]
+    pub signature_chain_id: u64,
+    pub hyperliquid_chain: String,
+    pub destination: String,
+    pub amount: Decimal,
+    pub time: u64,
+}
+
+impl UsdSend {
+    pub fn new(chain: HyperliquidChain, destination: Address, amount: Decimal, time: u64) -> Self {
+        Self {
+            signature_chain_id: DEFAULT_SIGNATURE_CHAIN_ID,
+            hyperliquid_chain: chain.hyperliquid_chain().to_string(),
+            destination: format!("{destination:#x}"),
+            amount,
+            time,
+        }
+    }
+}
+
+/// Withdraw USDC to Arbitrum through the bridge
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Withdraw3 {
+    #[serde(serialize_with = "serialize_chain_id")]
+    pub signature_chain_id: u64,
+    pub hyperliquid_chain: String,
+    pub destination: String,
+    pub amount: Decimal,
+    pub time: u64,
+}
+
+impl Withdraw3 {
+    pub fn new(chain: HyperliquidChain, destination: Address, amount: Decimal, time: u64) -> Self {
+        Self {
+            signature_chain_id: DEFAULT_SIGNATURE_CHAIN_ID,
+            hyperliquid_chain: chain.hyperliquid_chain().to_string(),
+            destination: format!("{destination:#x}"),
+            amount,
+            time,
+        }
+    }
+}
+
+/// Transfer a spot token to another address. `token` is `NAME:tokenId`, e.g.
+/// `PURR:0xc4bf3f870c0e9465323c0b6ed28096c2`
+#[derive(Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
-pub struct TransferRequest {
+pub struct SpotSend {
+    #[serde(serialize_with = "serialize_chain_id")]
+    pub signature_chain_id: u64,
+    pub hyperliquid_chain: String,
     pub destination: String,
-    pub amount: String,
+    pub token: String,
+    pub amount: Decimal,
     pub time: u64,
 }
 
-pub mod exchange_sol {
-    use alloy::core::sol;
-    sol! {
-        struct UsdTransferSignPayload {
-            string destination;
-            string amount;
-            uint64 time;
+impl SpotSend {
+    pub fn new(
+        chain: HyperliquidChain,
+        destination: Address,
+        token: impl Into<String>,
+        amount: Decimal,
+        time: u64,
+    ) -> Self {
+        Self {
+            signature_chain_id: DEFAULT_SIGNATURE_CHAIN_ID,
+            hyperliquid_chain: chain.hyperliquid_chain().to_string(),
+            destination: format!("{destination:#x}"),
+            token: token.into(),
+            amount,
+            time,
+        }
+    }
+}
+
+/// Move USDC between the spot and perp balances of the same account
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UsdClassTransfer {
+    #[serde(serialize_with = "serialize_chain_id")]
+    pub signature_chain_id: u64,
+    pub hyperliquid_chain: String,
+    pub amount: Decimal,
+    pub to_perp: bool,
+    pub nonce: u64,
+}
+
+impl UsdClassTransfer {
+    pub fn new(chain: HyperliquidChain, amount: Decimal, to_perp: bool, nonce: u64) -> Self {
+        Self {
+            signature_chain_id: DEFAULT_SIGNATURE_CHAIN_ID,
+            hyperliquid_chain: chain.hyperliquid_chain().to_string(),
+            amount,
+            to_perp,
+            nonce,
+        }
+    }
+}
+
+/// Authorize an API wallet to sign L1 actions for the master account
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ApproveAgent {
+    #[serde(serialize_with = "serialize_chain_id")]
+    pub signature_chain_id: u64,
+    pub hyperliquid_chain: String,
+    pub agent_address: Address,
+    /// Unnamed agents are signed with an empty name and sent without the field
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_name: Option<String>,
+    pub nonce: u64,
+}
+
+impl ApproveAgent {
+    pub fn new(
+        chain: HyperliquidChain,
+        agent_address: Address,
+        agent_name: Option<String>,
+        nonce: u64,
+    ) -> Self {
+        Self {
+            signature_chain_id: DEFAULT_SIGNATURE_CHAIN_ID,
+            hyperliquid_chain: chain.hyperliquid_chain().to_string(),
+            agent_address,
+            agent_name,
+            nonce,
         }
     }
 }
 
+impl UserSignedAction for UsdSend {
+    const EIP712_TYPE: &'static str = "HyperliquidTransaction:UsdSend(string hyperliquidChain,string destination,string amount,uint64 time)";
+    fn signature_chain_id(&self) -> u64 {
+        self.signature_chain_id
+    }
+    fn nonce(&self) -> u64 {
+        self.time
+    }
+    fn eip712_data_words(&self) -> Vec<B256> {
+        vec![
+            self.hyperliquid_chain.eip712_data_word(),
+            self.destination.eip712_data_word(),
+            self.amount.to_string().eip712_data_word(),
+            self.time.eip712_data_word(),
+        ]
+    }
+}
+
+impl UserSignedAction for Withdraw3 {
+    const EIP712_TYPE: &'static str = "HyperliquidTransaction:Withdraw(string hyperliquidChain,string destination,string amount,uint64 time)";
+    fn signature_chain_id(&self) -> u64 {
+        self.signature_chain_id
+    }
+    fn nonce(&self) -> u64 {
+        self.time
+    }
+    fn eip712_data_words(&self) -> Vec<B256> {
+        vec![
+            self.hyperliquid_chain.eip712_data_word(),
+            self.destination.eip712_data_word(),
+            self.amount.to_string().eip712_data_word(),
+            self.time.eip712_data_word(),
+        ]
+    }
+}
+
+impl UserSignedAction for SpotSend {
+    const EIP712_TYPE: &'static str = "HyperliquidTransaction:SpotSend(string hyperliquidChain,string destination,string token,string amount,uint64 time)";
+    fn signature_chain_id(&self) -> u64 {
+        self.signature_chain_id
+    }
+    fn nonce(&self) -> u64 {
+        self.time
+    }
+    fn eip712_data_words(&self) -> Vec<B256> {
+        vec![
+            self.hyperliquid_chain.eip712_data_word(),
+            self.destination.eip712_data_word(),
+            self.token.eip712_data_word(),
+            self.amount.to_string().eip712_data_word(),
+            self.time.eip712_data_word(),
+        ]
+    }
+}
+
+impl UserSignedAction for UsdClassTransfer {
+    const EIP712_TYPE: &'static str = "HyperliquidTransaction:UsdClassTransfer(string hyperliquidChain,string amount,bool toPerp,uint64 nonce)";
+    fn signature_chain_id(&self) -> u64 {
+        self.signature_chain_id
+    }
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+    fn eip712_data_words(&self) -> Vec<B256> {
+        vec![
+            self.hyperliquid_chain.eip712_data_word(),
+            self.amount.to_string().eip712_data_word(),
+            self.to_perp.eip712_data_word(),
+            self.nonce.eip712_data_word(),
+        ]
+    }
+}
+
+impl UserSignedAction for ApproveAgent {
+    const EIP712_TYPE: &'static str = "HyperliquidTransaction:ApproveAgent(string hyperliquidChain,address agentAddress,string agentName,uint64 nonce)";
+    fn signature_chain_id(&self) -> u64 {
+        self.signature_chain_id
+    }
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+    fn eip712_data_words(&self) -> Vec<B256> {
+        vec![
+            self.hyperliquid_chain.eip712_data_word(),
+            self.agent_address.eip712_data_word(),
+            self.agent_name
+                .clone()
+                .unwrap_or_default()
+                .eip712_data_word(),
+            self.nonce.eip712_data_word(),
+        ]
+    }
+}
+
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase", tag = "type")]
 pub enum Action {
@@ -147,14 +419,41 @@ pub enum Action {
     CancelByCloid {
         cancels: Vec<RequestCancelByClientId>,
     },
-    UsdTransfer {
-        chain: HyperliquidChain,
-        payload: TransferRequest,
+    Modify {
+        oid: OrderId,
+        order: HyperliquidOrderRequest,
     },
-    Withdraw {
-        usd: String,
-        nonce: u64,
+    BatchModify {
+        modifies: Vec<ModifyRequest>,
+    },
+    CreateSubAccount {
+        name: String,
+    },
+    /// Move `usd` (micro USDC) between the master account and a sub-account
+    #[serde(rename_all = "camelCase")]
+    SubAccountTransfer {
+        #[serde(serialize_with = "serialize_address")]
+        sub_account_user: Address,
+        is_deposit: bool,
+        usd: u64,
+    },
+    /// Deposit `usd` (micro USDC) into a vault or withdraw it
+    #[serde(rename_all = "camelCase")]
+    VaultTransfer {
+        #[serde(serialize_with = "serialize_address")]
+        vault_address: Address,
+        is_deposit: bool,
+        usd: u64,
     },
+    /// Cancel all open orders at `time` (ms). `None` removes a previously scheduled cancel
+    ScheduleCancel {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        time: Option<u64>,
+    },
+    UsdSend(UsdSend),
+    Withdraw3(Withdraw3),
+    SpotSend(SpotSend),
+    UsdClassTransfer(UsdClassTransfer),
     #[serde(rename_all = "camelCase")]
     UpdateLeverage {
         asset: u32,
@@ -167,12 +466,7 @@ pub enum Action {
         is_buy: bool,
         ntli: i64,
     },
-    #[serde(rename_all = "camelCase", rename = "connect")]
-    ApproveAgent {
-        chain: HyperliquidChain,
-        agent: agent::Agent,
-        agent_address: Address,
-    },
+    ApproveAgent(ApproveAgent),
     // But it belongs to info
     UserPoints {
         user: Address,
@@ -201,101 +495,170 @@ impl Action {
 pub enum WsExecutionResponse {
     #[serde(rename = "orderUpdates")]
     OrderUpdates(Vec<WsOrderUpdate>),
+    /// `userEvents` subscription
     #[serde(rename = "user")]
     User(WsUserEvent),
+    #[serde(rename = "userFills")]
+    UserFills(WsUserFills),
+    #[serde(rename = "userFundings")]
+    UserFundings(WsUserFundings),
+    #[serde(rename = "userNonFundingLedgerUpdates")]
+    UserNonFundingLedgerUpdates(WsLedgerUpdates),
     #[serde(rename = "error")]
     Error(String),
     #[serde(rename = "notification")]
-    Notification(String),
+    Notification(WsNotification),
     #[serde(other)]
     Other,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct WsOrderUpdate {
+    /// e.g. `open`, `filled`, `canceled`, `triggered`, `rejected` or `marginCanceled`
     pub status: String,
     pub status_timestamp: u64,
     pub order: WsOrder,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct WsOrder {
     pub coin: String,
     pub side: String,
-    #[serde(default)]
-    pub sz: f64,
-    #[serde(default)]
-    pub orig_sz: f64,
-    pub limit_px: Option<String>,
-    pub oid: String,
+    /// Remaining size
+    pub sz: Decimal,
+    pub orig_sz: Decimal,
+    pub limit_px: Decimal,
+    pub oid: u64,
     pub timestamp: u64,
+    #[serde(default)]
     pub cloid: Option<String>,
 }
 
-#[allow(non_snake_case)]
+/// Each `user` message carries one kind of event, keyed by its name
 #[derive(Debug, Deserialize)]
-#[serde(tag = "type")]
+#[serde(rename_all = "camelCase")]
 pub enum WsUserEvent {
-    #[serde(rename = "fills")]
-    Fills { fills: Vec<WsUserFill> },
-    #[serde(rename = "funding")]
-    Funding { funding: WsUserFunding },
-    #[serde(rename = "liquidation")]
-    Liquidation { liquidation: WsUserLiquidation },
-    #[serde(rename = "nonUserCancel")]
-    NonUserCancel { nonUserCancel: WsUserNonUserCancel },
-    #[serde(other)]
-    Other,
+    Fills(Vec<WsUserFill>),
+    Funding(WsUserFunding),
+    Liquidation(WsUserLiquidation),
+    NonUserCancel(Vec<WsUserNonUserCancel>),
 }
 
-#[derive(Debug, Deserialize)]
-pub struct WsUserFill {
+/// Fills have the same shape as those returned by the info endpoint
+pub type WsUserFill = crate::rest::models::UserFill;
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WsUserFunding {
     pub coin: String,
-    pub px: f64,
-    pub sz: f64,
-    pub side: String,
+    pub usdc: Decimal,
     pub time: u64,
-    pub start_position: String,
-    pub dir: String,
-    pub closed_pnl: String,
-    pub hash: String,
+    /// Signed position size the funding was paid on
+    pub szi: Decimal,
+    pub funding_rate: Decimal,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct WsUserLiquidation {
+    pub lid: u64,
+    pub liquidator: Address,
+    pub liquidated_user: Address,
+    pub liquidated_ntl_pos: Decimal,
+    pub liquidated_account_value: Decimal,
+}
+
+/// An order cancelled by the exchange rather than the user, e.g. for lack of margin
+#[derive(Debug, Deserialize, Clone)]
+pub struct WsUserNonUserCancel {
+    pub coin: String,
     pub oid: u64,
-    pub crossed: bool,
-    pub fee: String,
-    pub tid: u64,
-    pub cloid: String,
-    pub fee_token: String,
 }
 
-impl WsUserFill {
-    pub fn dir(&self) -> (String, String) {
-        let parts: Vec<&str> = self.dir.split(' ').collect();
-        if parts.len() == 2 {
-            (parts[0].to_string(), parts[1].to_string())
-        } else {
-            (self.dir.clone(), "".to_string())
-        }
-    }
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WsUserFills {
+    /// Set on the first message after subscribing, which replays recent fills
+    #[serde(default)]
+    pub is_snapshot: bool,
+    pub user: Address,
+    pub fills: Vec<WsUserFill>,
 }
 
 #[derive(Debug, Deserialize)]
-pub struct WsUserFunding {
-    pub coin: String,
-    pub usdc: f64,
-    pub time: u64,
+#[serde(rename_all = "camelCase")]
+pub struct WsUserFundings {
+    #[serde(default)]
+    pub is_snapshot: bool,
+    pub user: Address,
+    pub fundings: Vec<WsUserFunding>,
 }
 
 #[derive(Debug, Deserialize)]
-pub struct WsUserLiquidation {
+#[serde(rename_all = "camelCase")]
+pub struct WsLedgerUpdates {
+    #[serde(default)]
+    pub is_snapshot: bool,
+    pub user: Address,
+    pub non_funding_ledger_updates: Vec<WsLedgerUpdate>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct WsLedgerUpdate {
     pub time: u64,
+    pub hash: String,
+    pub delta: LedgerDelta,
+}
+
+/// Balance change of a ledger update, other than funding
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum LedgerDelta {
+    Deposit {
+        usdc: Decimal,
+    },
+    Withdraw {
+        usdc: Decimal,
+        fee: Decimal,
+        nonce: u64,
+    },
+    InternalTransfer {
+        usdc: Decimal,
+        user: Address,
+        destination: Address,
+        fee: Decimal,
+    },
+    SubAccountTransfer {
+        usdc: Decimal,
+        user: Address,
+        destination: Address,
+    },
+    #[serde(rename_all = "camelCase")]
+    AccountClassTransfer {
+        usdc: Decimal,
+        to_perp: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    SpotTransfer {
+        token: String,
+        amount: Decimal,
+        usdc_value: Decimal,
+        user: Address,
+        destination: Address,
+        fee: Decimal,
+    },
+    VaultDeposit {
+        vault: Address,
+        usdc: Decimal,
+    },
+    #[serde(other)]
+    Other,
 }
 
 #[derive(Debug, Deserialize)]
-pub struct WsUserNonUserCancel {
-    pub coin: String,
-    pub oid: u64,
-    pub reason: String,
-    pub time: u64,
+pub struct WsNotification {
+    pub notification: String,
 }
 
 // Request structs
@@ -317,41 +680,45 @@ pub struct HyperliquidRequestUserPoints {
     pub action: Action, // UserPoints only
 }
 
-// WebSocket Request for Execution
-#[derive(Debug, Serialize)]
-pub struct WsRequest {
-    pub method: Method,
-    #[serde(flatten)]
-    pub subscription: Subscription,
-}
-
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub enum Method {
-    Subscribe,
-    Unsubscribe,
-}
-
-#[derive(Debug, Serialize, Clone)]
-#[serde(tag = "channel")]
-pub enum Subscription {
-    #[serde(rename = "orderUpdates")]
-    OrderUpdates { user: Address },
-    #[serde(rename = "user")]
-    User { user: Address },
-}
+// Subscriptions are shared with market data
+pub use crate::market::models::{Method, Subscription, WsRequest};
 
 // Response models
 #[derive(Debug, Deserialize)]
+#[serde(tag = "status", content = "response", rename_all = "camelCase")]
 pub enum Response {
     Ok(OkResponse),
     Err(String),
 }
 
+impl Response {
+    pub fn into_ok(self) -> Result<OkResponse> {
+        match self {
+            Response::Ok(ok) => Ok(ok),
+            Response::Err(err) => Err(Error::ExchangeError(err)),
+        }
+    }
+    pub fn into_statuses(self) -> Result<Vec<Status>> {
+        match self.into_ok()?.data {
+            Some(ResponseData::Statuses(data)) => Ok(data.statuses),
+            _ => Ok(vec![]),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct OkResponse {
-    pub status: String,
-    pub data: Option<StatusData>,
+    #[serde(rename = "type")]
+    pub response_type: String,
+    pub data: Option<ResponseData>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ResponseData {
+    Statuses(StatusData),
+    /// e.g. the address of a newly created sub-account
+    Other(serde_json::Value),
 }
 
 #[derive(Debug, Deserialize)]
@@ -360,40 +727,35 @@ pub struct StatusData {
 }
 
 #[derive(Debug, Deserialize, Clone)]
-#[serde(tag = "type")]
+#[serde(rename_all = "camelCase")]
 pub enum Status {
-    #[serde(rename = "filled")]
     Filled(Filled),
-    #[serde(rename = "resting")]
     Resting(Resting),
-    #[serde(rename = "error")]
     Error(String),
-    #[serde(rename = "success")]
     Success,
-    #[serde(rename = "canceled")]
     Canceled,
-    #[serde(rename = "triggered")]
     Triggered,
-    #[serde(rename = "marginCanceled")]
     MarginCanceled,
-    #[serde(rename = "liquidation")]
     Liquidation,
-    #[serde(rename = "waitingForFill")]
     WaitingForFill,
-    #[serde(rename = "waitingForTrigger")]
     WaitingForTrigger,
     #[serde(other)]
     Other,
 }
 
 #[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct Filled {
     pub oid: u64,
-    pub total_sz: String,
-    pub avg_px: String,
+    pub total_sz: Decimal,
+    pub avg_px: Decimal,
+    #[serde(default)]
+    pub cloid: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Resting {
     pub oid: u64,
+    #[serde(default)]
+    pub cloid: Option<String>,
 }
@@ -0,0 +1,132 @@
+use crate::error::Result;
+use crate::utils::get_timestamp_ms;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+
+/// Hands out unique, strictly increasing millisecond nonces for a signer.
+///
+/// Nonces follow the wall clock, corrected by the last observed server time. When several
+/// nonces are drawn within the same millisecond, later ones are bumped past the previous one.
+/// Clones share state, so one manager can be given to every client signing with the same key.
+#[derive(Debug, Clone, Default)]
+pub struct NonceManager {
+    inner: Arc<NonceState>,
+}
+
+#[derive(Debug, Default)]
+struct NonceState {
+    last: AtomicU64,
+    /// server time minus local time, in milliseconds
+    skew_ms: AtomicI64,
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Draw the next nonce
+    pub fn next(&self) -> Result<u64> {
+        let now = get_timestamp_ms()?.saturating_add_signed(self.skew_ms());
+        let previous = self
+            .inner
+            .last
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |last| {
+                Some(now.max(last + 1))
+            })
+            .expect("update closure always returns Some");
+        Ok(now.max(previous + 1))
+    }
+
+    /// The most recently issued nonce, zero if none was issued yet
+    pub fn last(&self) -> u64 {
+        self.inner.last.load(Ordering::Acquire)
+    }
+
+    /// Record a server timestamp, e.g. from a WebSocket message, and return the measured skew
+    /// (server minus local) in milliseconds. Subsequent nonces are corrected by it.
+    pub fn observe_server_time(&self, server_time_ms: u64) -> Result<i64> {
+        let skew = server_time_ms as i64 - get_timestamp_ms()? as i64;
+        self.inner.skew_ms.store(skew, Ordering::Release);
+        Ok(skew)
+    }
+
+    /// Last measured clock skew (server minus local) in milliseconds
+    pub fn skew_ms(&self) -> i64 {
+        self.inner.skew_ms.load(Ordering::Acquire)
+    }
+
+    /// Whether the local clock is off from the server by more than `tolerance_ms`
+    pub fn is_skewed(&self, tolerance_ms: u64) -> bool {
+        self.skew_ms().unsigned_abs() > tolerance_ms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_nonces_increase_within_a_millisecond() {
+        let nonces = NonceManager::new();
+        let drawn: Vec<u64> = (0..1000).map(|_| nonces.next().unwrap()).collect();
+        assert!(drawn.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(nonces.last(), *drawn.last().unwrap());
+        // far more nonces than milliseconds elapsed, yet still close to the clock
+        let now = get_timestamp_ms().unwrap();
+        assert!(drawn[0] <= now);
+        assert!(nonces.last() < now + 1000);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_tasks_draw_unique_nonces() {
+        let nonces = NonceManager::new();
+        let tasks: Vec<_> = (0..16)
+            .map(|_| {
+                // every task holds its own clone, as separate clients would
+                let nonces = nonces.clone();
+                tokio::spawn(async move {
+                    let mut drawn = vec![];
+                    for _ in 0..500 {
+                        drawn.push(nonces.next().unwrap());
+                        tokio::task::yield_now().await;
+                    }
+                    drawn
+                })
+            })
+            .collect();
+
+        let mut all = HashSet::new();
+        for task in tasks {
+            let drawn = task.await.unwrap();
+            assert!(drawn.windows(2).all(|w| w[0] < w[1]));
+            all.extend(drawn);
+        }
+        assert_eq!(all.len(), 16 * 500);
+        assert_eq!(nonces.last(), *all.iter().max().unwrap());
+    }
+
+    #[test]
+    fn test_server_time_skew() {
+        let nonces = NonceManager::new();
+        assert!(!nonces.is_skewed(0));
+
+        // server clock an hour ahead of ours
+        let hour = 3_600_000;
+        let skew = nonces
+            .observe_server_time(get_timestamp_ms().unwrap() + hour)
+            .unwrap();
+        assert!((hour as i64 - 1000..=hour as i64).contains(&skew));
+        assert!(nonces.is_skewed(5000));
+        assert!(nonces.next().unwrap() >= get_timestamp_ms().unwrap() + hour - 1000);
+
+        // the clock correcting itself never moves nonces backwards
+        let before = nonces.last();
+        nonces
+            .observe_server_time(get_timestamp_ms().unwrap())
+            .unwrap();
+        assert!(!nonces.is_skewed(5000));
+        assert!(nonces.next().unwrap() > before);
+    }
+}
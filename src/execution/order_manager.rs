@@ -0,0 +1,698 @@
+use crate::Decimal;
+use crate::error::{Error, Result};
+use crate::execution::models::{
+    HyperliquidOrderRequest, HyperliquidOrderType, HyperliquidTif, Status, WsOrderUpdate,
+    WsUserFill,
+};
+use crate::execution::ws::UserEvent;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderState {
+    /// Submitted, not acknowledged yet
+    PendingNew,
+    Resting,
+    PartiallyFilled,
+    /// A trigger order whose trigger price was reached
+    Triggered,
+    Filled,
+    Canceled,
+    Rejected,
+}
+
+impl OrderState {
+    /// Whether the order may still trade
+    pub fn is_open(&self) -> bool {
+        !self.is_terminal()
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            OrderState::Filled | OrderState::Canceled | OrderState::Rejected
+        )
+    }
+
+    /// States only move forward, so a late acknowledgement never undoes a later update
+    fn rank(&self) -> u8 {
+        match self {
+            OrderState::PendingNew => 0,
+            OrderState::Resting => 1,
+            OrderState::Triggered => 2,
+            OrderState::PartiallyFilled => 3,
+            OrderState::Filled | OrderState::Canceled | OrderState::Rejected => 4,
+        }
+    }
+
+    /// State of an `orderUpdates` status, e.g. `open`, `filled` or `marginCanceled`
+    fn from_update_status(status: &str) -> Option<Self> {
+        match status {
+            "open" => Some(OrderState::Resting),
+            "filled" => Some(OrderState::Filled),
+            "triggered" => Some(OrderState::Triggered),
+            "canceled" | "scheduledCancel" => Some(OrderState::Canceled),
+            "rejected" => Some(OrderState::Rejected),
+            // e.g. `reduceOnlyCanceled` or `tickRejected`
+            s if s.ends_with("Canceled") => Some(OrderState::Canceled),
+            s if s.ends_with("Rejected") => Some(OrderState::Rejected),
+            _ => None,
+        }
+    }
+}
+
+/// Local handle of an order tracked by an [`OrderManager`], assigned before the exchange
+/// knows about it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct OrderKey(u64);
+
+#[derive(Debug, Clone)]
+pub struct TrackedOrder {
+    pub key: OrderKey,
+    pub coin: String,
+    pub oid: Option<u64>,
+    pub cloid: Option<String>,
+    pub is_buy: bool,
+    pub limit_px: Decimal,
+    pub orig_sz: Decimal,
+    pub filled_sz: Decimal,
+    pub state: OrderState,
+    /// Rejection error, or the status that cancelled the order
+    pub reason: Option<String>,
+    /// Time (ms) of the latest `orderUpdates` status
+    pub updated_at: Option<u64>,
+    /// unfilled remainder is cancelled right away
+    immediate: bool,
+    /// size by trade id, so repeated fills are counted once
+    fills: HashMap<u64, Decimal>,
+}
+
+impl TrackedOrder {
+    pub fn remaining_sz(&self) -> Result<Decimal> {
+        self.orig_sz.checked_sub(self.filled_sz).ok_or_else(|| {
+            Error::DecimalOverflow(format!("{} - {} filled", self.orig_sz, self.filled_sz))
+        })
+    }
+
+    fn advance(&mut self, state: OrderState) {
+        if !self.state.is_terminal() && state.rank() >= self.state.rank() {
+            self.state = state;
+        }
+    }
+
+    /// Record a lower bound of the filled size, from any source
+    fn observe_filled(&mut self, filled_sz: Decimal) {
+        self.filled_sz = self.filled_sz.max(filled_sz);
+        if self.filled_sz >= self.orig_sz {
+            self.advance(OrderState::Filled);
+        } else if !self.filled_sz.is_zero() {
+            self.advance(OrderState::PartiallyFilled);
+        }
+    }
+
+    fn add_fill(&mut self, tid: u64, sz: Decimal) {
+        // a total that overflows is no better than the lower bound already kept
+        if self.fills.insert(tid, sz).is_none()
+            && let Some(filled) = self
+                .fills
+                .values()
+                .try_fold(Decimal::ZERO, |total, sz| total.checked_add(*sz))
+        {
+            self.observe_filled(filled);
+        }
+    }
+
+    /// Fold in what was learned about the same order before it could be matched
+    fn merge(&mut self, other: TrackedOrder) {
+        for (tid, sz) in other.fills {
+            self.add_fill(tid, sz);
+        }
+        self.observe_filled(other.filled_sz);
+        self.advance(other.state);
+        if other.state == self.state {
+            self.reason = self.reason.take().or(other.reason);
+        }
+        self.cloid = self.cloid.take().or(other.cloid);
+        self.updated_at = self.updated_at.max(other.updated_at);
+    }
+}
+
+/// In-memory book of orders and their lifecycle.
+///
+/// Orders are registered with [`Self::submit`] before they are sent, then updated from the
+/// statuses of the REST response and from `orderUpdates` and fill events. These may arrive in
+/// any order: updates for an order id that is not known yet are tracked on their own and merged
+/// once the acknowledgement links that id to a submitted order. Orders placed elsewhere, e.g.
+/// from another process, are picked up from `orderUpdates` as well.
+#[derive(Debug, Default)]
+pub struct OrderManager {
+    next_key: u64,
+    orders: HashMap<OrderKey, TrackedOrder>,
+    by_oid: HashMap<u64, OrderKey>,
+    by_cloid: HashMap<String, OrderKey>,
+    /// fills whose order is not known yet, by oid
+    orphan_fills: HashMap<u64, HashMap<u64, Decimal>>,
+    /// oids of the orphan fills that were already waiting at the last [`Self::remove_closed`]
+    stale_orphans: HashSet<u64>,
+}
+
+impl OrderManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start tracking an order that is about to be sent
+    pub fn submit(&mut self, coin: impl Into<String>, order: &HyperliquidOrderRequest) -> OrderKey {
+        let immediate = matches!(
+            order.order_type,
+            HyperliquidOrderType::Limit {
+                tif: HyperliquidTif::Ioc | HyperliquidTif::FrontendMarket
+            }
+        );
+        self.insert(TrackedOrder {
+            key: OrderKey(0),
+            coin: coin.into(),
+            oid: None,
+            cloid: order.cloid.as_deref().map(str::to_lowercase),
+            is_buy: order.is_buy,
+            limit_px: order.limit_px,
+            orig_sz: order.sz,
+            filled_sz: Decimal::ZERO,
+            state: OrderState::PendingNew,
+            reason: None,
+            updated_at: None,
+            immediate,
+            fills: HashMap::new(),
+        })
+    }
+
+    fn insert(&mut self, mut order: TrackedOrder) -> OrderKey {
+        let key = OrderKey(self.next_key);
+        self.next_key += 1;
+        order.key = key;
+        if let Some(cloid) = &order.cloid {
+            self.by_cloid.insert(cloid.clone(), key);
+        }
+        self.orders.insert(key, order);
+        key
+    }
+
+    /// Apply the statuses of a `place_orders` response; `keys` are the submitted orders in the
+    /// order they were sent
+    pub fn on_statuses(&mut self, keys: &[OrderKey], statuses: &[Status]) {
+        for (key, status) in keys.iter().zip(statuses) {
+            self.on_status(*key, status);
+        }
+    }
+
+    /// Apply the REST status of a single submitted order
+    pub fn on_status(&mut self, key: OrderKey, status: &Status) {
+        match status {
+            Status::Resting(resting) => {
+                self.link_oid(key, resting.oid);
+                self.update(key, |order| order.advance(OrderState::Resting));
+            }
+            Status::Filled(filled) => {
+                self.link_oid(key, filled.oid);
+                self.update(key, |order| {
+                    order.observe_filled(filled.total_sz);
+                    if order.immediate {
+                        order.advance(OrderState::Canceled);
+                    }
+                });
+            }
+            Status::WaitingForFill | Status::WaitingForTrigger => {
+                self.update(key, |order| order.advance(OrderState::Resting));
+            }
+            Status::Triggered => self.update(key, |order| order.advance(OrderState::Triggered)),
+            Status::Error(error) => self.update(key, |order| {
+                order.advance(OrderState::Rejected);
+                order.reason = Some(error.clone());
+            }),
+            _ => {}
+        }
+    }
+
+    /// Apply an `orderUpdates` event
+    pub fn on_order_update(&mut self, update: &WsOrderUpdate) {
+        let ws = &update.order;
+        let cloid = ws.cloid.as_deref().map(str::to_lowercase);
+        let known = self.by_oid.get(&ws.oid).copied().or_else(|| {
+            let key = *self.by_cloid.get(cloid.as_ref()?)?;
+            // an order already linked to another oid is a different order reusing the cloid
+            self.orders[&key].oid.is_none().then_some(key)
+        });
+        let key = match known {
+            Some(key) => key,
+            None => self.insert(TrackedOrder {
+                key: OrderKey(0),
+                coin: ws.coin.clone(),
+                oid: None,
+                cloid,
+                is_buy: ws.side == "B",
+                limit_px: ws.limit_px,
+                orig_sz: ws.orig_sz,
+                filled_sz: Decimal::ZERO,
+                state: OrderState::PendingNew,
+                reason: None,
+                updated_at: None,
+                immediate: false,
+                fills: HashMap::new(),
+            }),
+        };
+        self.link_oid(key, ws.oid);
+
+        let state = OrderState::from_update_status(&update.status);
+        self.update(key, |order| {
+            if let Some(filled) = ws.orig_sz.checked_sub(ws.sz) {
+                order.observe_filled(filled);
+            }
+            if let Some(state) = state {
+                order.advance(state);
+                if order.state == state && state.is_terminal() && state != OrderState::Filled {
+                    order.reason = Some(update.status.clone());
+                }
+            }
+            order.updated_at = order.updated_at.max(Some(update.status_timestamp));
+        });
+    }
+
+    /// Apply a fill. Fills are counted once per trade id
+    pub fn on_fill(&mut self, fill: &WsUserFill) {
+        let key = self.by_oid.get(&fill.oid).copied().or_else(|| {
+            let cloid = fill.cloid.as_deref()?.to_lowercase();
+            let key = *self.by_cloid.get(&cloid)?;
+            self.orders[&key].oid.is_none().then_some(key)
+        });
+        match key {
+            Some(key) => {
+                self.link_oid(key, fill.oid);
+                self.update(key, |order| order.add_fill(fill.tid, fill.sz));
+            }
+            None => {
+                self.orphan_fills
+                    .entry(fill.oid)
+                    .or_default()
+                    .insert(fill.tid, fill.sz);
+            }
+        }
+    }
+
+    /// Apply the order related events of a [`crate::execution::ws::UserEventStream`]
+    pub fn on_user_event(&mut self, event: &UserEvent) {
+        match event {
+            UserEvent::OrderUpdates(updates) => {
+                for update in updates {
+                    self.on_order_update(update);
+                }
+            }
+            UserEvent::Fills(fills) => {
+                for fill in fills {
+                    self.on_fill(fill);
+                }
+            }
+            UserEvent::NonUserCancels(cancels) => {
+                for cancel in cancels {
+                    // like an early update, a cancel for an unknown oid is tracked on its own
+                    // until the ack links it; only its coin is known
+                    let key = match self.by_oid.get(&cancel.oid) {
+                        Some(&key) => key,
+                        None => {
+                            let key = self.insert(TrackedOrder {
+                                key: OrderKey(0),
+                                coin: cancel.coin.clone(),
+                                oid: None,
+                                cloid: None,
+                                is_buy: false,
+                                limit_px: Decimal::ZERO,
+                                orig_sz: Decimal::ZERO,
+                                filled_sz: Decimal::ZERO,
+                                state: OrderState::PendingNew,
+                                reason: None,
+                                updated_at: None,
+                                immediate: false,
+                                fills: HashMap::new(),
+                            });
+                            self.link_oid(key, cancel.oid);
+                            key
+                        }
+                    };
+                    self.update(key, |order| {
+                        order.advance(OrderState::Canceled);
+                        order
+                            .reason
+                            .get_or_insert_with(|| "nonUserCancel".to_string());
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Attach an exchange order id to an order, merging whatever was tracked under that id
+    fn link_oid(&mut self, key: OrderKey, oid: u64) {
+        if let Some(&other) = self.by_oid.get(&oid)
+            && other != key
+            && let Some(other) = self.orders.remove(&other)
+        {
+            if let Some(cloid) = &other.cloid
+                && self.by_cloid.get(cloid) == Some(&other.key)
+            {
+                self.by_cloid.insert(cloid.clone(), key);
+            }
+            self.update(key, |order| order.merge(other));
+        }
+        self.by_oid.insert(oid, key);
+        let orphans = self.orphan_fills.remove(&oid);
+        self.update(key, |order| {
+            order.oid = Some(oid);
+            for (tid, sz) in orphans.into_iter().flatten() {
+                order.add_fill(tid, sz);
+            }
+        });
+    }
+
+    fn update(&mut self, key: OrderKey, f: impl FnOnce(&mut TrackedOrder)) {
+        if let Some(order) = self.orders.get_mut(&key) {
+            f(order);
+        }
+    }
+
+    pub fn get(&self, key: OrderKey) -> Option<&TrackedOrder> {
+        self.orders.get(&key)
+    }
+    pub fn get_by_oid(&self, oid: u64) -> Option<&TrackedOrder> {
+        self.get(*self.by_oid.get(&oid)?)
+    }
+    pub fn get_by_cloid(&self, cloid: &str) -> Option<&TrackedOrder> {
+        self.get(*self.by_cloid.get(&cloid.to_lowercase())?)
+    }
+
+    /// Orders of `coin` that may still trade, oldest first
+    pub fn open_orders(&self, coin: &str) -> Vec<&TrackedOrder> {
+        let mut orders: Vec<_> = self
+            .orders
+            .values()
+            .filter(|order| order.coin == coin && order.state.is_open())
+            .collect();
+        orders.sort_by_key(|order| order.key);
+        orders
+    }
+
+    /// Every order that may still trade, grouped by coin
+    pub fn all_open_orders(&self) -> HashMap<&str, Vec<&TrackedOrder>> {
+        let mut by_coin: HashMap<&str, Vec<&TrackedOrder>> = HashMap::new();
+        for order in self.orders.values().filter(|order| order.state.is_open()) {
+            by_coin.entry(&order.coin).or_default().push(order);
+        }
+        for orders in by_coin.values_mut() {
+            orders.sort_by_key(|order| order.key);
+        }
+        by_coin
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &TrackedOrder> {
+        self.orders.values()
+    }
+
+    /// Stop tracking filled, cancelled and rejected orders and return how many were dropped.
+    ///
+    /// Fills that could not be matched are dropped as well once no submitted order is waiting
+    /// for its oid, or when they were already waiting at the previous call: those belong to
+    /// orders placed elsewhere or already removed.
+    pub fn remove_closed(&mut self) -> usize {
+        let awaiting_ack = self.orders.values().any(|order| order.oid.is_none());
+        let stale = std::mem::take(&mut self.stale_orphans);
+        self.orphan_fills
+            .retain(|oid, _| awaiting_ack && !stale.contains(oid));
+        self.stale_orphans = self.orphan_fills.keys().copied().collect();
+
+        let closed: Vec<_> = self
+            .orders
+            .values()
+            .filter(|order| order.state.is_terminal())
+            .map(|order| (order.key, order.oid, order.cloid.clone()))
+            .collect();
+        for (key, oid, cloid) in &closed {
+            self.orders.remove(key);
+            if let Some(oid) = oid {
+                self.by_oid.remove(oid);
+            }
+            if let Some(cloid) = cloid
+                && self.by_cloid.get(cloid) == Some(key)
+            {
+                self.by_cloid.remove(cloid);
+            }
+        }
+        closed.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::models::{Filled, Resting, TpSl};
+
+    const CLOID: &str = "0x00000000000000000000000000000001";
+
+    fn d(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    fn order(tif: HyperliquidTif, cloid: Option<&str>) -> HyperliquidOrderRequest {
+        HyperliquidOrderRequest {
+            asset: 1,
+            is_buy: true,
+            limit_px: d("2400"),
+            sz: d("1"),
+            reduce_only: false,
+            order_type: HyperliquidOrderType::Limit { tif },
+            cloid: cloid.map(str::to_string),
+        }
+    }
+
+    fn update(status: &str, oid: u64, cloid: Option<&str>, remaining: &str) -> WsOrderUpdate {
+        serde_json::from_value(serde_json::json!({
+            "order": {"coin": "ETH", "side": "B", "limitPx": "2400.0", "sz": remaining, "oid": oid, "timestamp": 1, "origSz": "1.0", "cloid": cloid},
+            "status": status,
+            "statusTimestamp": 1700000000000u64 + oid,
+        }))
+        .unwrap()
+    }
+
+    fn fill(oid: u64, tid: u64, sz: &str, cloid: Option<&str>) -> WsUserFill {
+        serde_json::from_value(serde_json::json!({
+            "coin": "ETH", "px": "2400.0", "sz": sz, "side": "B", "time": 1,
+            "startPosition": "0.0", "dir": "Open Long", "closedPnl": "0.0",
+            "hash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "oid": oid, "crossed": false, "fee": "0.1", "tid": tid, "feeToken": "USDC", "cloid": cloid
+        }))
+        .unwrap()
+    }
+
+    fn resting(oid: u64) -> Status {
+        Status::Resting(Resting { oid, cloid: None })
+    }
+
+    #[test]
+    fn test_ack_then_updates() {
+        let mut orders = OrderManager::new();
+        let key = orders.submit("ETH", &order(HyperliquidTif::Gtc, Some(CLOID)));
+        assert_eq!(orders.get(key).unwrap().state, OrderState::PendingNew);
+        assert_eq!(orders.open_orders("ETH").len(), 1);
+
+        orders.on_statuses(&[key], &[resting(11)]);
+        assert_eq!(orders.get_by_oid(11).unwrap().state, OrderState::Resting);
+
+        orders.on_order_update(&update("open", 11, Some(CLOID), "1.0"));
+        orders.on_fill(&fill(11, 100, "0.4", Some(CLOID)));
+        // the same fill delivered twice
+        orders.on_fill(&fill(11, 100, "0.4", Some(CLOID)));
+        let tracked = orders.get_by_cloid(CLOID).unwrap();
+        assert_eq!(tracked.state, OrderState::PartiallyFilled);
+        assert_eq!(tracked.filled_sz, d("0.4"));
+        assert_eq!(tracked.remaining_sz().unwrap(), d("0.6"));
+
+        // the update reports the fill as well; it is not counted twice
+        orders.on_order_update(&update("open", 11, Some(CLOID), "0.6"));
+        assert_eq!(orders.get(key).unwrap().filled_sz, d("0.4"));
+
+        orders.on_fill(&fill(11, 101, "0.6", Some(CLOID)));
+        orders.on_order_update(&update("filled", 11, Some(CLOID), "0.0"));
+        let tracked = orders.get(key).unwrap();
+        assert_eq!(tracked.state, OrderState::Filled);
+        assert_eq!(tracked.filled_sz, d("1"));
+        assert_eq!(tracked.updated_at, Some(1700000000011));
+        assert!(orders.open_orders("ETH").is_empty());
+    }
+
+    #[test]
+    fn test_updates_before_ack() {
+        let mut orders = OrderManager::new();
+        // without a cloid, the update cannot be matched until the ack links the oid
+        let key = orders.submit("ETH", &order(HyperliquidTif::Gtc, None));
+        orders.on_order_update(&update("open", 12, None, "1.0"));
+        assert_eq!(orders.iter().count(), 2);
+
+        orders.on_status(key, &resting(12));
+        assert_eq!(orders.iter().count(), 1);
+        let tracked = orders.get_by_oid(12).unwrap();
+        assert_eq!(tracked.key, key);
+        assert_eq!(tracked.state, OrderState::Resting);
+        assert_eq!(tracked.updated_at, Some(1700000000012));
+
+        // a fill for an unknown order waits for its oid
+        let key = orders.submit("ETH", &order(HyperliquidTif::Gtc, None));
+        orders.on_fill(&fill(13, 200, "1.0", None));
+        assert_eq!(orders.get(key).unwrap().state, OrderState::PendingNew);
+        orders.on_status(
+            key,
+            &Status::Filled(Filled {
+                oid: 13,
+                total_sz: d("1"),
+                avg_px: d("2400"),
+                cloid: None,
+            }),
+        );
+        let tracked = orders.get(key).unwrap();
+        assert_eq!(tracked.state, OrderState::Filled);
+        assert_eq!(tracked.filled_sz, d("1"));
+
+        // with a cloid, a cancel that overtakes the ack is matched right away
+        let key = orders.submit("ETH", &order(HyperliquidTif::Gtc, Some(CLOID)));
+        orders.on_order_update(&update("marginCanceled", 14, Some(CLOID), "1.0"));
+        orders.on_status(key, &resting(14));
+        let tracked = orders.get(key).unwrap();
+        assert_eq!(tracked.state, OrderState::Canceled);
+        assert_eq!(tracked.reason.as_deref(), Some("marginCanceled"));
+    }
+
+    #[test]
+    fn test_trigger_before_ack() {
+        let mut orders = OrderManager::new();
+        let mut stop = order(HyperliquidTif::Gtc, Some(CLOID));
+        stop.order_type = HyperliquidOrderType::Trigger {
+            is_market: true,
+            trigger_px: d("2400"),
+            tpsl: TpSl::Sl,
+        };
+        let key = orders.submit("ETH", &stop);
+        orders.on_order_update(&update("triggered", 15, Some(CLOID), "1.0"));
+        orders.on_status(key, &Status::WaitingForTrigger);
+        assert_eq!(orders.get(key).unwrap().state, OrderState::Triggered);
+
+        orders.on_fill(&fill(15, 300, "0.5", Some(CLOID)));
+        assert_eq!(orders.get(key).unwrap().state, OrderState::PartiallyFilled);
+    }
+
+    #[test]
+    fn test_non_user_cancel_before_ack() {
+        let mut orders = OrderManager::new();
+        let key = orders.submit("ETH", &order(HyperliquidTif::Gtc, None));
+        orders.on_user_event(&UserEvent::NonUserCancels(
+            serde_json::from_value(serde_json::json!([{"coin": "ETH", "oid": 16}])).unwrap(),
+        ));
+        orders.on_status(key, &resting(16));
+        assert_eq!(orders.iter().count(), 1);
+        let tracked = orders.get_by_oid(16).unwrap();
+        assert_eq!(tracked.key, key);
+        assert_eq!(tracked.state, OrderState::Canceled);
+        assert_eq!(tracked.reason.as_deref(), Some("nonUserCancel"));
+        assert_eq!(tracked.orig_sz, d("1"));
+    }
+
+    #[test]
+    fn test_remove_closed_prunes_orphan_fills() {
+        let mut orders = OrderManager::new();
+        let key = orders.submit("ETH", &order(HyperliquidTif::Ioc, None));
+        orders.on_statuses(
+            &[key],
+            &[Status::Filled(Filled {
+                oid: 41,
+                total_sz: d("1"),
+                avg_px: d("2400"),
+                cloid: None,
+            })],
+        );
+        assert_eq!(orders.remove_closed(), 1);
+        // a late fill of the removed order, and one of an order placed elsewhere
+        orders.on_fill(&fill(41, 400, "1.0", None));
+        orders.on_fill(&fill(42, 401, "1.0", None));
+        assert_eq!(orders.orphan_fills.len(), 2);
+        orders.remove_closed();
+        assert!(orders.orphan_fills.is_empty());
+
+        // while an ack is outstanding, orphans survive one call
+        let pending = orders.submit("ETH", &order(HyperliquidTif::Gtc, None));
+        orders.on_fill(&fill(43, 402, "0.5", None));
+        orders.remove_closed();
+        assert_eq!(orders.orphan_fills.len(), 1);
+        orders.on_status(pending, &resting(43));
+        assert_eq!(orders.get(pending).unwrap().filled_sz, d("0.5"));
+
+        orders.submit("ETH", &order(HyperliquidTif::Gtc, None));
+        orders.on_fill(&fill(44, 403, "0.5", None));
+        orders.remove_closed();
+        orders.remove_closed();
+        assert!(orders.orphan_fills.is_empty());
+    }
+
+    #[test]
+    fn test_rejected_and_immediate_orders() {
+        let mut orders = OrderManager::new();
+        let keys = [
+            orders.submit("ETH", &order(HyperliquidTif::Alo, None)),
+            orders.submit("ETH", &order(HyperliquidTif::Ioc, None)),
+        ];
+        orders.on_statuses(
+            &keys,
+            &[
+                Status::Error("Post only order would have immediately matched".to_string()),
+                Status::Filled(Filled {
+                    oid: 21,
+                    total_sz: d("0.25"),
+                    avg_px: d("2400"),
+                    cloid: None,
+                }),
+            ],
+        );
+        let rejected = orders.get(keys[0]).unwrap();
+        assert_eq!(rejected.state, OrderState::Rejected);
+        assert!(rejected.reason.as_ref().unwrap().starts_with("Post only"));
+        // the rest of an IOC order is cancelled
+        let ioc = orders.get(keys[1]).unwrap();
+        assert_eq!(ioc.state, OrderState::Canceled);
+        assert_eq!(ioc.filled_sz, d("0.25"));
+
+        // a late update does not reopen a closed order
+        orders.on_order_update(&update("open", 21, None, "0.75"));
+        assert_eq!(orders.get(keys[1]).unwrap().state, OrderState::Canceled);
+    }
+
+    #[test]
+    fn test_open_orders_per_coin() {
+        let mut orders = OrderManager::new();
+        let eth = orders.submit("ETH", &order(HyperliquidTif::Gtc, None));
+        let btc = orders.submit("BTC", &order(HyperliquidTif::Gtc, None));
+        orders.on_statuses(&[eth, btc], &[resting(31), resting(32)]);
+        // an order placed elsewhere shows up through its updates
+        let mut external = update("open", 33, None, "1.0");
+        external.order.coin = "BTC".to_string();
+        orders.on_order_update(&external);
+
+        let btc_orders: Vec<_> = orders
+            .open_orders("BTC")
+            .iter()
+            .map(|order| order.oid)
+            .collect();
+        assert_eq!(btc_orders, [Some(32), Some(33)]);
+        assert_eq!(orders.all_open_orders()["ETH"].len(), 1);
+
+        orders.on_user_event(&UserEvent::NonUserCancels(
+            serde_json::from_value(serde_json::json!([{"coin": "ETH", "oid": 31}])).unwrap(),
+        ));
+        assert!(orders.open_orders("ETH").is_empty());
+        assert_eq!(orders.remove_closed(), 1);
+        assert!(orders.get_by_oid(31).is_none());
+        assert_eq!(orders.iter().count(), 2);
+    }
+}
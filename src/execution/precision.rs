@@ -0,0 +1,243 @@
+use crate::Decimal;
+use crate::decimal::RoundingMode;
+use crate::error::{Error, Result};
+use crate::execution::models::{HyperliquidOrderRequest, HyperliquidOrderType};
+use crate::rest::models::{Asset, SpotToken};
+
+/// Prices may have at most this many significant figures, unless they are integers
+pub const MAX_SIG_FIGS: u32 = 5;
+/// Price decimals plus `szDecimals` may not exceed this for perps
+pub const MAX_DECIMALS_PERP: u32 = 6;
+/// Price decimals plus `szDecimals` may not exceed this for spot
+pub const MAX_DECIMALS_SPOT: u32 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarketKind {
+    Perp,
+    Spot,
+}
+
+/// Tick and lot rules of a single asset, derived from its `szDecimals`.
+///
+/// Rounded values are normalized, so they can be used as-is in an order and hash canonically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriceSizeRules {
+    pub kind: MarketKind,
+    pub sz_decimals: u32,
+}
+
+impl PriceSizeRules {
+    pub fn perp(sz_decimals: u32) -> Self {
+        Self {
+            kind: MarketKind::Perp,
+            sz_decimals,
+        }
+    }
+    pub fn spot(sz_decimals: u32) -> Self {
+        Self {
+            kind: MarketKind::Spot,
+            sz_decimals,
+        }
+    }
+    pub fn from_asset(asset: &Asset) -> Self {
+        Self::perp(asset.szDecimals)
+    }
+    /// Spot pairs are sized in their base token
+    pub fn from_spot_base_token(token: &SpotToken) -> Self {
+        Self::spot(token.szDecimals)
+    }
+
+    /// Maximum number of decimals a price may have
+    pub fn max_price_decimals(&self) -> u32 {
+        let max_decimals = match self.kind {
+            MarketKind::Perp => MAX_DECIMALS_PERP,
+            MarketKind::Spot => MAX_DECIMALS_SPOT,
+        };
+        max_decimals.saturating_sub(self.sz_decimals)
+    }
+
+    /// Round a price to at most 5 significant figures and [`Self::max_price_decimals`] decimals.
+    /// Integer prices are always accepted, so large prices round to whole numbers
+    pub fn round_price(&self, px: Decimal, mode: RoundingMode) -> Decimal {
+        let decimals = px
+            .significant_decimals(MAX_SIG_FIGS)
+            .min(self.max_price_decimals());
+        px.round_to(decimals, mode).normalize()
+    }
+
+    /// Round a size to `szDecimals` decimals
+    pub fn round_size(&self, sz: Decimal, mode: RoundingMode) -> Decimal {
+        sz.round_to(self.sz_decimals, mode).normalize()
+    }
+
+    pub fn validate_price(&self, px: Decimal) -> Result<()> {
+        if !px.is_sign_positive() {
+            return Err(Error::InvalidOrder(format!("price {px} must be positive")));
+        }
+        let is_integer = px.normalize().scale() == 0;
+        if !is_integer && px.normalize().scale() > px.significant_decimals(MAX_SIG_FIGS) {
+            return Err(Error::InvalidOrder(format!(
+                "price {px} has more than {MAX_SIG_FIGS} significant figures"
+            )));
+        }
+        if px.normalize().scale() > self.max_price_decimals() {
+            return Err(Error::InvalidOrder(format!(
+                "price {px} has more than {} decimals",
+                self.max_price_decimals()
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn validate_size(&self, sz: Decimal) -> Result<()> {
+        if !sz.is_sign_positive() {
+            return Err(Error::InvalidOrder(format!("size {sz} must be positive")));
+        }
+        if sz.normalize().scale() > self.sz_decimals {
+            return Err(Error::InvalidOrder(format!(
+                "size {sz} has more than {} decimals",
+                self.sz_decimals
+            )));
+        }
+        Ok(())
+    }
+
+    /// Round the limit price, trigger price and size of an order, then validate the result.
+    /// Fails if the size rounds down to zero
+    pub fn round_order(
+        &self,
+        mut order: HyperliquidOrderRequest,
+        px_mode: RoundingMode,
+        sz_mode: RoundingMode,
+    ) -> Result<HyperliquidOrderRequest> {
+        order.limit_px = self.round_price(order.limit_px, px_mode);
+        if let HyperliquidOrderType::Trigger { trigger_px, .. } = &mut order.order_type {
+            *trigger_px = self.round_price(*trigger_px, px_mode);
+        }
+        order.sz = self.round_size(order.sz, sz_mode);
+        self.validate_order(&order)?;
+        Ok(order)
+    }
+
+    /// Check the limit price, trigger price and size of an order against these rules
+    pub fn validate_order(&self, order: &HyperliquidOrderRequest) -> Result<()> {
+        self.validate_price(order.limit_px)?;
+        if let HyperliquidOrderType::Trigger { trigger_px, .. } = &order.order_type {
+            self.validate_price(*trigger_px)?;
+        }
+        self.validate_size(order.sz)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::models::{HyperliquidTif, TpSl};
+    use RoundingMode::*;
+
+    fn d(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_round_perp_price() {
+        // BTC: szDecimals 5, so at most 1 price decimal
+        let btc = PriceSizeRules::perp(5);
+        assert_eq!(btc.max_price_decimals(), 1);
+        assert_eq!(btc.round_price(d("65432.1"), Down).to_string(), "65432");
+        assert_eq!(btc.round_price(d("65432.6"), Nearest).to_string(), "65433");
+        assert_eq!(btc.round_price(d("65432.1"), Up).to_string(), "65433");
+        assert_eq!(btc.round_price(d("1234.56"), Nearest).to_string(), "1234.6");
+        assert_eq!(btc.round_price(d("123456.7"), Down).to_string(), "123456");
+
+        // a small-cap perp with szDecimals 0 keeps up to 6 decimals
+        let meme = PriceSizeRules::perp(0);
+        assert_eq!(
+            meme.round_price(d("0.000123456"), Down).to_string(),
+            "0.000123"
+        );
+        assert_eq!(
+            meme.round_price(d("0.0123456"), Nearest).to_string(),
+            "0.012346"
+        );
+        assert_eq!(
+            meme.round_price(d("0.0123456"), Down).to_string(),
+            "0.012345"
+        );
+    }
+
+    #[test]
+    fn test_round_spot_price_and_size() {
+        let spot = PriceSizeRules::spot(2);
+        assert_eq!(spot.max_price_decimals(), 6);
+        assert_eq!(
+            spot.round_price(d("0.00012345678"), Down).to_string(),
+            "0.000123"
+        );
+        assert_eq!(
+            PriceSizeRules::spot(0)
+                .round_price(d("0.00012345678"), Down)
+                .to_string(),
+            "0.00012345"
+        );
+        assert_eq!(spot.round_size(d("1.239"), Down).to_string(), "1.23");
+        assert_eq!(spot.round_size(d("1.231"), Up).to_string(), "1.24");
+        assert_eq!(spot.round_size(d("1.200"), Nearest).to_string(), "1.2");
+    }
+
+    #[test]
+    fn test_validate() {
+        let eth = PriceSizeRules::perp(4);
+        assert!(eth.validate_price(d("2700")).is_ok());
+        assert!(eth.validate_price(d("2700.0")).is_ok());
+        assert!(eth.validate_price(d("2700.5")).is_ok());
+        assert!(eth.validate_price(d("1234567")).is_ok());
+        assert!(eth.validate_price(d("2700.55")).is_err());
+        assert!(eth.validate_price(d("12.345")).is_err());
+        assert!(eth.validate_price(d("0")).is_err());
+        assert!(eth.validate_size(d("0.0031")).is_ok());
+        assert!(eth.validate_size(d("0.00031")).is_err());
+        assert!(eth.validate_size(d("-1")).is_err());
+
+        let order = HyperliquidOrderRequest {
+            asset: 1,
+            is_buy: true,
+            limit_px: d("2700"),
+            sz: d("0.0031"),
+            reduce_only: false,
+            order_type: HyperliquidOrderType::Trigger {
+                is_market: true,
+                trigger_px: d("2650.25"),
+                tpsl: TpSl::Sl,
+            },
+            cloid: None,
+        };
+        let err = eth.validate_order(&order).unwrap_err();
+        assert!(matches!(err, Error::InvalidOrder(_)), "{err:?}");
+        let order = HyperliquidOrderRequest {
+            order_type: HyperliquidOrderType::Limit {
+                tif: HyperliquidTif::Gtc,
+            },
+            ..order
+        };
+        assert!(eth.validate_order(&order).is_ok());
+
+        let rounded = eth.round_order(
+            HyperliquidOrderRequest {
+                limit_px: d("2700.55"),
+                sz: d("0.00319"),
+                ..order.clone()
+            },
+            Up,
+            Down,
+        );
+        let rounded = rounded.unwrap();
+        assert_eq!(rounded.limit_px.to_string(), "2700.6");
+        assert_eq!(rounded.sz.to_string(), "0.0031");
+        let too_small = HyperliquidOrderRequest {
+            sz: d("0.00001"),
+            ..order
+        };
+        assert!(eth.round_order(too_small, Nearest, Down).is_err());
+    }
+}
@@ -0,0 +1,400 @@
+use crate::HyperliquidUrls;
+use crate::error::{Error, Result};
+use crate::execution::models::{
+    HyperliquidChain, WsExecutionResponse, WsLedgerUpdate, WsOrderUpdate, WsUserEvent, WsUserFill,
+    WsUserFunding, WsUserLiquidation, WsUserNonUserCancel,
+};
+use crate::market::models::Subscription;
+use crate::market::ws::{HyperliquidWsClient, WsEvent, WsOptions};
+use crate::rest::info::{FillOrder, HyperliquidInfoClient};
+use crate::utils::get_timestamp_ms;
+use alloy::primitives::Address;
+use futures::{Stream, StreamExt, TryStreamExt, stream};
+use std::collections::{HashSet, VecDeque};
+
+/// Number of recent fill ids remembered to drop fills delivered more than once
+const RECENT_FILLS: usize = 10_000;
+
+/// An event of the user followed by a [`UserEventStream`]
+#[derive(Debug)]
+pub enum UserEvent {
+    OrderUpdates(Vec<WsOrderUpdate>),
+    /// Fills not seen before, whether from `userEvents`, `userFills` or a backfill
+    Fills(Vec<WsUserFill>),
+    Fundings(Vec<WsUserFunding>),
+    Liquidation(WsUserLiquidation),
+    NonUserCancels(Vec<WsUserNonUserCancel>),
+    LedgerUpdates(Vec<WsLedgerUpdate>),
+    /// The connection was re-established after up to `gap_ms` without messages. Missed fills
+    /// were already backfilled, but order updates may be missing, so open orders should be
+    /// resynced.
+    Reconnected {
+        gap_ms: u64,
+    },
+}
+
+/// Fill ids already delivered, and the time of the latest fill to backfill from
+struct FillTracker {
+    seen: HashSet<u64>,
+    recent: VecDeque<u64>,
+    /// when the stream connected; older fills in the first snapshot are history
+    start_time: u64,
+    last_time: u64,
+    /// whether the `userFills` snapshot sent on subscribing was received
+    has_snapshot: bool,
+}
+
+impl FillTracker {
+    fn new(start_time: u64) -> Self {
+        Self {
+            seen: HashSet::new(),
+            recent: VecDeque::new(),
+            start_time,
+            last_time: start_time,
+            has_snapshot: false,
+        }
+    }
+
+    /// Keep the fills that were not seen before
+    fn retain_new(&mut self, fills: Vec<WsUserFill>) -> Vec<WsUserFill> {
+        fills
+            .into_iter()
+            .filter(|fill| {
+                if !self.seen.insert(fill.tid) {
+                    return false;
+                }
+                self.recent.push_back(fill.tid);
+                if self.recent.len() > RECENT_FILLS
+                    && let Some(oldest) = self.recent.pop_front()
+                {
+                    self.seen.remove(&oldest);
+                }
+                self.last_time = self.last_time.max(fill.time);
+                true
+            })
+            .collect()
+    }
+}
+
+/// Single feed of order lifecycle events for one user.
+///
+/// Subscribes to `orderUpdates`, `userEvents`, `userFills`, `userFundings` and
+/// `userNonFundingLedgerUpdates`. Fills arrive on both `userEvents` and `userFills`, so they are
+/// deduplicated by trade id. After a reconnect, fills missed in the meantime are fetched from
+/// the info endpoint before [`UserEvent::Reconnected`] is yielded.
+///
+/// The first `userFills` snapshot replays recent history, of which only fills made since
+/// connecting are yielded; older ones are just remembered so they are never delivered later.
+/// Funding and ledger snapshots are skipped as well; query the info endpoint for that history.
+pub struct UserEventStream {
+    user: Address,
+    ws: HyperliquidWsClient<WsExecutionResponse>,
+    info: HyperliquidInfoClient,
+    fills: FillTracker,
+    queued: VecDeque<Result<UserEvent>>,
+}
+
+impl UserEventStream {
+    pub async fn connect(chain: HyperliquidChain, user: Address) -> Result<Self> {
+        let config = HyperliquidUrls::from_chain(chain);
+
+        Self::connect_with_config(chain, &config, user).await
+    }
+    pub async fn connect_with_config(
+        chain: HyperliquidChain,
+        config: &HyperliquidUrls,
+        user: Address,
+    ) -> Result<Self> {
+        Self::connect_with_options(chain, config, user, WsOptions::default()).await
+    }
+    pub async fn connect_with_options(
+        chain: HyperliquidChain,
+        config: &HyperliquidUrls,
+        user: Address,
+        options: WsOptions,
+    ) -> Result<Self> {
+        let start_time = get_timestamp_ms()?;
+        let ws = HyperliquidWsClient::connect_typed(config, options).await?;
+        for subscription in [
+            Subscription::OrderUpdates { user },
+            Subscription::UserEvents { user },
+            Subscription::UserFills { user },
+            Subscription::UserFundings { user },
+            Subscription::UserNonFundingLedgerUpdates { user },
+        ] {
+            ws.subscribe(subscription).await?;
+        }
+        Ok(Self {
+            user,
+            ws,
+            info: HyperliquidInfoClient::new_with_config(chain, config),
+            fills: FillTracker::new(start_time),
+            queued: VecDeque::new(),
+        })
+    }
+
+    pub fn user(&self) -> Address {
+        self.user
+    }
+
+    /// Wait for the next event. `None` once the connection is lost for good
+    pub async fn next_event(&mut self) -> Option<Result<UserEvent>> {
+        loop {
+            if let Some(event) = self.queued.pop_front() {
+                return Some(event);
+            }
+            match self.ws.next().await? {
+                Ok(WsEvent::Message(message)) => {
+                    if let Some(event) = self.handle(message) {
+                        return Some(event);
+                    }
+                }
+                Ok(WsEvent::Reconnected { gap_ms }) => {
+                    match self.backfill().await {
+                        Ok(fills) if fills.is_empty() => {}
+                        Ok(fills) => self.queued.push_back(Ok(UserEvent::Fills(fills))),
+                        Err(err) => self.queued.push_back(Err(err)),
+                    }
+                    self.queued.push_back(Ok(UserEvent::Reconnected { gap_ms }));
+                }
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+
+    /// Turn the client into a [`Stream`] of events
+    pub fn into_stream(self) -> impl Stream<Item = Result<UserEvent>> {
+        stream::unfold(self, |mut this| async move {
+            let event = this.next_event().await?;
+            Some((event, this))
+        })
+    }
+
+    fn handle(&mut self, message: WsExecutionResponse) -> Option<Result<UserEvent>> {
+        let event = match message {
+            WsExecutionResponse::OrderUpdates(updates) => UserEvent::OrderUpdates(updates),
+            WsExecutionResponse::User(WsUserEvent::Fills(fills)) => self.new_fills(fills)?,
+            WsExecutionResponse::User(WsUserEvent::Funding(funding)) => {
+                UserEvent::Fundings(vec![funding])
+            }
+            WsExecutionResponse::User(WsUserEvent::Liquidation(liquidation)) => {
+                UserEvent::Liquidation(liquidation)
+            }
+            WsExecutionResponse::User(WsUserEvent::NonUserCancel(cancels)) => {
+                UserEvent::NonUserCancels(cancels)
+            }
+            WsExecutionResponse::UserFills(update) => {
+                let mut fills = update.fills;
+                if update.is_snapshot && !self.fills.has_snapshot {
+                    self.fills.has_snapshot = true;
+                    let start_time = self.fills.start_time;
+                    let (history, recent) =
+                        fills.into_iter().partition(|fill| fill.time < start_time);
+                    self.fills.retain_new(history);
+                    fills = recent;
+                }
+                self.new_fills(fills)?
+            }
+            WsExecutionResponse::UserFundings(fundings) if !fundings.is_snapshot => {
+                UserEvent::Fundings(fundings.fundings)
+            }
+            WsExecutionResponse::UserNonFundingLedgerUpdates(updates) if !updates.is_snapshot => {
+                UserEvent::LedgerUpdates(updates.non_funding_ledger_updates)
+            }
+            WsExecutionResponse::Error(error) => return Some(Err(Error::ExchangeError(error))),
+            _ => return None,
+        };
+        Some(Ok(event))
+    }
+
+    fn new_fills(&mut self, fills: Vec<WsUserFill>) -> Option<UserEvent> {
+        let fills = self.fills.retain_new(fills);
+        (!fills.is_empty()).then_some(UserEvent::Fills(fills))
+    }
+
+    /// Fetch fills since the latest one seen
+    async fn backfill(&mut self) -> Result<Vec<WsUserFill>> {
+        let fills: Vec<WsUserFill> = self
+            .info
+            .user_fills_history(
+                self.user,
+                self.fills.last_time,
+                None,
+                FillOrder::OldestFirst,
+            )
+            .try_collect()
+            .await?;
+        Ok(self.fills.retain_new(fills))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::models::LedgerDelta;
+    use crate::market::ws::tests::{ack, send_json, serve};
+    use crate::rest::helper::tests::serve_sequence;
+    use serde_json::Value;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    const USER: &str = "0x5e9ee1089755c3435139848e47e6635505d5a13a";
+
+    fn fill(time: u64, tid: u64) -> Value {
+        serde_json::json!({
+            "coin": "ETH", "px": "2443.1", "sz": "0.01", "side": "B", "time": time,
+            "startPosition": "0.0", "dir": "Open Long", "closedPnl": "0.0",
+            "hash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "oid": 7, "crossed": true, "fee": "0.010994", "tid": tid, "feeToken": "USDC"
+        })
+    }
+
+    fn user_fills(is_snapshot: bool, fills: Vec<Value>) -> Value {
+        serde_json::json!({"channel": "userFills", "data": {"isSnapshot": is_snapshot, "user": USER, "fills": fills}})
+    }
+
+    fn tids(event: Option<Result<UserEvent>>) -> Vec<u64> {
+        match event.unwrap().unwrap() {
+            UserEvent::Fills(fills) => fills.iter().map(|fill| fill.tid).collect(),
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_user_events_dedupe_and_backfill() -> eyre::Result<()> {
+        let now = get_timestamp_ms()?;
+        // fills pushed after the snapshot, well after connecting
+        let later = now + 60_000;
+        let connections = Arc::new(AtomicUsize::new(0));
+        let counter = connections.clone();
+        let mut config = serve(move |mut socket| {
+            let connection = counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                let mut channels = vec![];
+                for _ in 0..5 {
+                    let request = ack(&mut socket).await;
+                    assert_eq!(request["subscription"]["user"], USER);
+                    channels.push(request["subscription"]["type"].as_str().unwrap().to_string());
+                }
+                assert_eq!(
+                    channels,
+                    [
+                        "orderUpdates",
+                        "userEvents",
+                        "userFills",
+                        "userFundings",
+                        "userNonFundingLedgerUpdates"
+                    ]
+                );
+                if connection == 0 {
+                    // history from before connecting, and a fill made while subscribing
+                    let subscribed = get_timestamp_ms().unwrap();
+                    send_json(&mut socket, user_fills(true, vec![fill(now - 5000, 1), fill(subscribed, 4)])).await;
+                    send_json(&mut socket, serde_json::json!({"channel": "userFundings", "data": {"isSnapshot": true, "user": USER, "fundings": [{"time": now - 3600000, "coin": "ETH", "usdc": "-0.05", "szi": "1.0", "fundingRate": "0.0000125"}]}})).await;
+                    send_json(&mut socket, serde_json::json!({"channel": "orderUpdates", "data": [{"order": {"coin": "ETH", "side": "B", "limitPx": "2443.1", "sz": "0.02", "oid": 7, "timestamp": now, "origSz": "0.02", "cloid": "0x00000000000000000000000000000001"}, "status": "open", "statusTimestamp": now}]})).await;
+                    send_json(&mut socket, serde_json::json!({"channel": "user", "data": {"fills": [fill(later + 1, 2)]}})).await;
+                    // the same fill again on the fills channel
+                    send_json(&mut socket, user_fills(false, vec![fill(later + 1, 2)])).await;
+                    send_json(&mut socket, serde_json::json!({"channel": "user", "data": {"nonUserCancel": [{"coin": "ETH", "oid": 7}]}})).await;
+                    drop(socket);
+                } else {
+                    // the reconnect snapshot repeats fills that were already backfilled
+                    send_json(&mut socket, user_fills(true, vec![fill(later + 1, 2), fill(later + 2, 3)])).await;
+                    send_json(&mut socket, serde_json::json!({"channel": "userNonFundingLedgerUpdates", "data": {"user": USER, "nonFundingLedgerUpdates": [{"time": now + 3, "hash": "0x0000000000000000000000000000000000000000000000000000000000000000", "delta": {"type": "accountClassTransfer", "usdc": "100.0", "toPerp": true}}]}})).await;
+                    let _ = socket.next().await;
+                }
+            }
+        })
+        .await;
+        let (host, mut requests) = serve_sequence(vec![serde_json::to_string(&[
+            fill(later + 1, 2),
+            fill(later + 2, 3),
+        ])?])
+        .await;
+        config.set_rest_endpoint(host);
+
+        let options = WsOptions {
+            min_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(50),
+            max_reconnect_attempts: Some(20),
+            ping_interval: None,
+            ..WsOptions::default()
+        };
+        let user: Address = USER.parse()?;
+        let mut events =
+            UserEventStream::connect_with_options(HyperliquidChain::Dev, &config, user, options)
+                .await?;
+
+        assert_eq!(tids(events.next_event().await), [4]);
+        match events.next_event().await.unwrap()? {
+            UserEvent::OrderUpdates(updates) => {
+                let update = &updates[0];
+                assert_eq!((update.status.as_str(), update.order.oid), ("open", 7));
+                assert_eq!(update.order.orig_sz, "0.02".parse()?);
+                assert_eq!(update.order.limit_px, "2443.1".parse()?);
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+        assert_eq!(tids(events.next_event().await), [2]);
+        match events.next_event().await.unwrap()? {
+            UserEvent::NonUserCancels(cancels) => assert_eq!(cancels[0].oid, 7),
+            other => panic!("unexpected event: {other:?}"),
+        }
+
+        // fill 3 happened while disconnected
+        assert_eq!(tids(events.next_event().await), [3]);
+        let backfill: Value = serde_json::from_str(&requests.recv().await.unwrap().body)?;
+        assert_eq!(backfill["type"], "userFillsByTime");
+        assert_eq!(backfill["startTime"], later + 1);
+        assert!(matches!(
+            events.next_event().await.unwrap()?,
+            UserEvent::Reconnected { .. }
+        ));
+
+        match events.next_event().await.unwrap()? {
+            UserEvent::LedgerUpdates(updates) => assert!(matches!(
+                updates[0].delta,
+                LedgerDelta::AccountClassTransfer { to_perp: true, .. }
+            )),
+            other => panic!("unexpected event: {other:?}"),
+        }
+        assert_eq!(connections.load(Ordering::SeqCst), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_user_event_messages() -> eyre::Result<()> {
+        let funding: WsExecutionResponse = serde_json::from_str(
+            r#"{"channel":"user","data":{"funding":{"time":1700000000000,"coin":"BTC","usdc":"-1.25","szi":"0.5","fundingRate":"0.0000125"}}}"#,
+        )?;
+        match funding {
+            WsExecutionResponse::User(WsUserEvent::Funding(funding)) => {
+                assert_eq!(funding.usdc, "-1.25".parse()?);
+                assert_eq!(funding.funding_rate, "0.0000125".parse()?);
+            }
+            other => panic!("unexpected message: {other:?}"),
+        }
+        let liquidation: WsExecutionResponse = serde_json::from_str(
+            r#"{"channel":"user","data":{"liquidation":{"lid":12,"liquidator":"0x0000000000000000000000000000000000000001","liquidated_user":"0x5e9ee1089755c3435139848e47e6635505d5a13a","liquidated_ntl_pos":"1000.0","liquidated_account_value":"12.5"}}}"#,
+        )?;
+        assert!(matches!(
+            liquidation,
+            WsExecutionResponse::User(WsUserEvent::Liquidation(WsUserLiquidation { lid: 12, .. }))
+        ));
+        let unknown: WsExecutionResponse = serde_json::from_str(
+            r#"{"channel":"userNonFundingLedgerUpdates","data":{"user":"0x5e9ee1089755c3435139848e47e6635505d5a13a","nonFundingLedgerUpdates":[{"time":1,"hash":"0x00","delta":{"type":"rewardsClaim","amount":"1.0"}}]}}"#,
+        )?;
+        match unknown {
+            WsExecutionResponse::UserNonFundingLedgerUpdates(updates) => {
+                assert!(!updates.is_snapshot);
+                assert!(matches!(
+                    updates.non_funding_ledger_updates[0].delta,
+                    LedgerDelta::Other
+                ));
+            }
+            other => panic!("unexpected message: {other:?}"),
+        }
+        Ok(())
+    }
+}
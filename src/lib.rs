@@ -1,7 +1,10 @@
+pub use decimal::Decimal;
+pub use rest::exchange::HyperliquidExchangeClient;
 pub use rest::info::HyperliquidInfoClient;
 pub use urls::HyperliquidUrls;
 
-mod error;
+pub mod decimal;
+pub mod error;
 
 pub mod execution;
 mod sign;
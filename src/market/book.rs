@@ -0,0 +1,414 @@
+use crate::Decimal;
+use crate::error::{Error, Result};
+use crate::market::models::{PriceLevel, WsBook};
+use std::cmp::{Ordering, Reverse};
+
+const HALF: Decimal = Decimal::new(5, 1);
+const ONE_BPS: Decimal = Decimal::new(1, 4);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookSide {
+    Bid,
+    Ask,
+}
+
+/// A price level whose size changed between two snapshots. A size of zero means the level
+/// was added (`old_sz`) or removed (`new_sz`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelChange {
+    pub side: BookSide,
+    pub px: Decimal,
+    pub old_sz: Decimal,
+    pub new_sz: Decimal,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BookDiff {
+    pub changes: Vec<LevelChange>,
+}
+
+impl BookDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// Local L2 book for a single coin, maintained from `l2Book` snapshots.
+///
+/// Bids are kept best (highest) first and asks best (lowest) first.
+#[derive(Debug, Clone)]
+pub struct OrderBook {
+    pub coin: String,
+    pub time: u64,
+    bids: Vec<PriceLevel>,
+    asks: Vec<PriceLevel>,
+}
+
+impl OrderBook {
+    pub fn new(coin: impl Into<String>) -> Self {
+        Self {
+            coin: coin.into(),
+            time: 0,
+            bids: vec![],
+            asks: vec![],
+        }
+    }
+    pub fn from_snapshot(book: &WsBook) -> Self {
+        let mut this = Self::new(book.coin.clone());
+        this.replace(book);
+        this
+    }
+
+    /// Replace the book with a new snapshot and report which levels changed. Snapshots of
+    /// another coin or older than the current book are rejected and leave it untouched
+    pub fn apply(&mut self, book: &WsBook) -> Result<BookDiff> {
+        if book.coin != self.coin {
+            return Err(Error::InvalidSnapshot(format!(
+                "{} snapshot applied to the {} book",
+                book.coin, self.coin
+            )));
+        }
+        if book.time < self.time {
+            return Err(Error::InvalidSnapshot(format!(
+                "{} snapshot at {} is older than the book at {}",
+                book.coin, book.time, self.time
+            )));
+        }
+        Ok(self.replace(book))
+    }
+
+    fn replace(&mut self, book: &WsBook) -> BookDiff {
+        let mut bids = book.levels.0.clone();
+        let mut asks = book.levels.1.clone();
+        bids.sort_by_key(|level| Reverse(level.px));
+        asks.sort_by_key(|level| level.px);
+
+        let mut diff = BookDiff::default();
+        diff_side(BookSide::Bid, &self.bids, &bids, &mut diff);
+        diff_side(BookSide::Ask, &self.asks, &asks, &mut diff);
+
+        self.time = book.time;
+        self.bids = bids;
+        self.asks = asks;
+        diff
+    }
+
+    pub fn bids(&self) -> &[PriceLevel] {
+        &self.bids
+    }
+    pub fn asks(&self) -> &[PriceLevel] {
+        &self.asks
+    }
+    fn levels(&self, side: BookSide) -> &[PriceLevel] {
+        match side {
+            BookSide::Bid => &self.bids,
+            BookSide::Ask => &self.asks,
+        }
+    }
+
+    pub fn best_bid(&self) -> Option<&PriceLevel> {
+        self.bids.first()
+    }
+    pub fn best_ask(&self) -> Option<&PriceLevel> {
+        self.asks.first()
+    }
+    /// `None` if a side is empty or the prices overflow
+    pub fn mid(&self) -> Option<Decimal> {
+        let sum = self.best_bid()?.px.checked_add(self.best_ask()?.px)?;
+        sum.checked_mul(HALF)
+    }
+    /// `None` if a side is empty or the prices overflow
+    pub fn spread(&self) -> Option<Decimal> {
+        self.best_ask()?.px.checked_sub(self.best_bid()?.px)
+    }
+    /// `None` if a side is empty or the prices overflow
+    pub fn spread_bps(&self) -> Option<Decimal> {
+        self.spread()?
+            .checked_div(self.mid()?.checked_mul(ONE_BPS)?)
+    }
+
+    /// Size resting at exactly `px`, zero if there is no such level
+    pub fn depth_at(&self, side: BookSide, px: Decimal) -> Decimal {
+        self.levels(side)
+            .iter()
+            .find(|level| level.px == px)
+            .map_or(Decimal::ZERO, |level| level.sz)
+    }
+
+    /// Total size on `side` priced within `bps` basis points of the mid, zero if a side is
+    /// empty
+    pub fn cumulative_size_within_bps(&self, side: BookSide, bps: Decimal) -> Result<Decimal> {
+        if self.bids.is_empty() || self.asks.is_empty() {
+            return Ok(Decimal::ZERO);
+        }
+        let overflow = || Error::DecimalOverflow(format!("{} book within {bps} bps", self.coin));
+        let mid = self.mid().ok_or_else(overflow)?;
+        let offset = mid
+            .checked_mul(bps)
+            .and_then(|offset| offset.checked_mul(ONE_BPS))
+            .ok_or_else(overflow)?;
+        let bound = match side {
+            BookSide::Bid => mid.checked_sub(offset),
+            BookSide::Ask => mid.checked_add(offset),
+        }
+        .ok_or_else(overflow)?;
+        self.levels(side)
+            .iter()
+            .take_while(|level| match side {
+                BookSide::Bid => level.px >= bound,
+                BookSide::Ask => level.px <= bound,
+            })
+            .try_fold(Decimal::ZERO, |total, level| total.checked_add(level.sz))
+            .ok_or_else(overflow)
+    }
+
+    /// Average price of filling `size` as a taker: buys walk the asks, sells walk the bids.
+    /// `None` if the book is too thin to fill the whole size.
+    pub fn vwap_to_fill(&self, is_buy: bool, size: Decimal) -> Result<Option<Decimal>> {
+        if size <= Decimal::ZERO {
+            return Ok(None);
+        }
+        let overflow = || Error::DecimalOverflow(format!("{} book vwap for {size}", self.coin));
+        let levels = if is_buy { &self.asks } else { &self.bids };
+        let mut remaining = size;
+        let mut notional = Decimal::ZERO;
+        for level in levels {
+            let take = remaining.min(level.sz);
+            notional = take
+                .checked_mul(level.px)
+                .and_then(|cost| notional.checked_add(cost))
+                .ok_or_else(overflow)?;
+            remaining = remaining.checked_sub(take).ok_or_else(overflow)?;
+            if remaining <= Decimal::ZERO {
+                return notional.checked_div(size).map(Some).ok_or_else(overflow);
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Merge two sides sorted best first and record every level whose size differs
+fn diff_side(side: BookSide, old: &[PriceLevel], new: &[PriceLevel], diff: &mut BookDiff) {
+    // order prices from best to worst for this side
+    let cmp = |a: Decimal, b: Decimal| match side {
+        BookSide::Bid => b.cmp(&a),
+        BookSide::Ask => a.cmp(&b),
+    };
+    let (mut i, mut j) = (0, 0);
+    loop {
+        let change = match (old.get(i), new.get(j)) {
+            (None, None) => break,
+            (Some(o), None) => {
+                i += 1;
+                (o.px, o.sz, Decimal::ZERO)
+            }
+            (None, Some(n)) => {
+                j += 1;
+                (n.px, Decimal::ZERO, n.sz)
+            }
+            (Some(o), Some(n)) => match cmp(o.px, n.px) {
+                Ordering::Less => {
+                    i += 1;
+                    (o.px, o.sz, Decimal::ZERO)
+                }
+                Ordering::Greater => {
+                    j += 1;
+                    (n.px, Decimal::ZERO, n.sz)
+                }
+                Ordering::Equal => {
+                    i += 1;
+                    j += 1;
+                    (o.px, o.sz, n.sz)
+                }
+            },
+        };
+        let (px, old_sz, new_sz) = change;
+        if old_sz != new_sz {
+            diff.changes.push(LevelChange {
+                side,
+                px,
+                old_sz,
+                new_sz,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    fn book(time: u64, bids: &[(&str, &str)], asks: &[(&str, &str)]) -> WsBook {
+        let levels = |levels: &[(&str, &str)]| {
+            levels
+                .iter()
+                .map(|&(px, sz)| PriceLevel {
+                    px: d(px),
+                    sz: d(sz),
+                    n: Some(1),
+                })
+                .collect()
+        };
+        WsBook {
+            coin: "BTC".to_string(),
+            time,
+            levels: (levels(bids), levels(asks)),
+        }
+    }
+
+    #[test]
+    fn test_top_of_book() -> eyre::Result<()> {
+        let book = OrderBook::from_snapshot(&book(
+            1,
+            &[("99", "2"), ("100", "1"), ("98", "5")],
+            &[("102", "3"), ("101", "1.5")],
+        ));
+        assert_eq!(book.best_bid().unwrap().px, d("100"));
+        assert_eq!(book.best_ask().unwrap().px, d("101"));
+        assert_eq!(book.mid(), Some(d("100.5")));
+        assert_eq!(book.spread(), Some(d("1")));
+        assert_eq!(book.spread_bps().unwrap().trunc_to(4), d("99.5024"));
+        assert_eq!(book.depth_at(BookSide::Bid, d("99.0")), d("2"));
+        assert_eq!(book.depth_at(BookSide::Ask, d("99")), Decimal::ZERO);
+
+        let empty = OrderBook::new("BTC");
+        assert_eq!(empty.mid(), None);
+        assert_eq!(
+            empty.cumulative_size_within_bps(BookSide::Bid, d("100"))?,
+            Decimal::ZERO
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_cumulative_size_and_vwap() -> eyre::Result<()> {
+        let book = OrderBook::from_snapshot(&book(
+            1,
+            &[("100", "1"), ("99", "2"), ("98", "5")],
+            &[("101", "1"), ("102", "2"), ("110", "5")],
+        ));
+        // mid 100.5, 100 bps = 1.005
+        assert_eq!(
+            book.cumulative_size_within_bps(BookSide::Bid, d("100"))?,
+            d("1")
+        );
+        assert_eq!(
+            book.cumulative_size_within_bps(BookSide::Ask, d("200"))?,
+            d("3")
+        );
+
+        assert_eq!(book.vwap_to_fill(true, d("1"))?, Some(d("101")));
+        assert_eq!(book.vwap_to_fill(true, d("2"))?, Some(d("101.5")));
+        assert_eq!(
+            book.vwap_to_fill(false, d("3"))?.unwrap().trunc_to(6),
+            d("99.333333")
+        );
+        assert_eq!(book.vwap_to_fill(false, d("9"))?, None);
+        assert_eq!(book.vwap_to_fill(true, Decimal::ZERO)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_overflowing_prices() {
+        let huge = i128::MAX.to_string();
+        let book = OrderBook::from_snapshot(&book(1, &[(&huge, "1")], &[(&huge, "2")]));
+        assert_eq!(book.mid(), None);
+        assert_eq!(book.spread(), Some(Decimal::ZERO));
+        assert!(matches!(
+            book.cumulative_size_within_bps(BookSide::Bid, d("10")),
+            Err(Error::DecimalOverflow(_))
+        ));
+        assert!(matches!(
+            book.vwap_to_fill(true, d("2")),
+            Err(Error::DecimalOverflow(_))
+        ));
+    }
+
+    #[test]
+    fn test_diff_between_snapshots() {
+        let mut ob = OrderBook::new("BTC");
+        let diff = ob
+            .apply(&book(1, &[("100", "1")], &[("101", "1")]))
+            .unwrap();
+        assert_eq!(diff.changes.len(), 2);
+
+        let diff = ob
+            .apply(&book(
+                2,
+                &[("100", "1"), ("99.5", "3")],
+                &[("101", "0.5"), ("102", "4")],
+            ))
+            .unwrap();
+        assert_eq!(
+            diff.changes,
+            vec![
+                LevelChange {
+                    side: BookSide::Bid,
+                    px: d("99.5"),
+                    old_sz: Decimal::ZERO,
+                    new_sz: d("3")
+                },
+                LevelChange {
+                    side: BookSide::Ask,
+                    px: d("101"),
+                    old_sz: d("1"),
+                    new_sz: d("0.5")
+                },
+                LevelChange {
+                    side: BookSide::Ask,
+                    px: d("102"),
+                    old_sz: Decimal::ZERO,
+                    new_sz: d("4")
+                },
+            ]
+        );
+
+        let diff = ob
+            .apply(&book(3, &[("99.5", "3")], &[("101", "0.5"), ("102", "4")]))
+            .unwrap();
+        assert_eq!(
+            diff.changes,
+            vec![LevelChange {
+                side: BookSide::Bid,
+                px: d("100"),
+                old_sz: d("1"),
+                new_sz: Decimal::ZERO
+            }]
+        );
+        assert_eq!(ob.time, 3);
+
+        // the same levels with a different string encoding are not a change
+        let diff = ob
+            .apply(&book(
+                4,
+                &[("99.50", "3.0")],
+                &[("101", "0.5"), ("102", "4")],
+            ))
+            .unwrap();
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn test_rejects_other_coin_and_stale_snapshots() {
+        let mut ob = OrderBook::from_snapshot(&book(5, &[("100", "1")], &[("101", "1")]));
+
+        let mut eth = book(6, &[("2400", "1")], &[("2401", "1")]);
+        eth.coin = "ETH".to_string();
+        assert!(matches!(ob.apply(&eth), Err(Error::InvalidSnapshot(_))));
+
+        let stale = book(4, &[("99", "1")], &[("101", "1")]);
+        assert!(matches!(ob.apply(&stale), Err(Error::InvalidSnapshot(_))));
+
+        assert_eq!(ob.time, 5);
+        assert_eq!(ob.best_bid().unwrap().px, d("100"));
+        assert!(
+            ob.apply(&book(5, &[("100", "1")], &[("101", "1")]))
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
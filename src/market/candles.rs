@@ -0,0 +1,605 @@
+use crate::Decimal;
+use crate::error::{Error, Result};
+use crate::market::models::{CandleInterval, CandleSnapshot, WsTrade};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// How trades are grouped into bars
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarSpec {
+    /// Time bars aligned like the exchange's candles
+    Interval(CandleInterval),
+    /// Time bars of any length in milliseconds, aligned to the epoch, e.g. 10 second bars
+    Millis(u64),
+    /// A bar closes once it traded this much size; larger trades are split across bars
+    Volume(Decimal),
+    /// A bar closes after this many trades
+    Ticks(u64),
+}
+
+impl BarSpec {
+    fn is_time(&self) -> bool {
+        matches!(self, BarSpec::Interval(_) | BarSpec::Millis(_))
+    }
+
+    fn open_time(&self, time: u64) -> u64 {
+        match self {
+            BarSpec::Interval(interval) => interval.open_time(time),
+            BarSpec::Millis(ms) => time / ms * ms,
+            _ => time,
+        }
+    }
+
+    fn next_open_time(&self, open_time: u64) -> u64 {
+        match self {
+            BarSpec::Interval(interval) => interval.next_open_time(open_time),
+            BarSpec::Millis(ms) => open_time + ms,
+            _ => open_time,
+        }
+    }
+}
+
+/// A closed OHLCV bar.
+///
+/// Time bars span `open_time..=close_time` like exchange candles; volume and tick bars span
+/// the times of their first and last trade.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bar {
+    pub open_time: u64,
+    pub close_time: u64,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    pub volume: Decimal,
+    pub trades: u64,
+}
+
+impl Bar {
+    pub fn into_candle(self, symbol: String, interval: CandleInterval) -> CandleSnapshot {
+        CandleSnapshot {
+            time_start: self.open_time,
+            time_end: self.close_time,
+            symbol,
+            interval,
+            open: self.open,
+            high: self.high,
+            low: self.low,
+            close: self.close,
+            volume: self.volume,
+            trades: self.trades,
+        }
+    }
+
+    /// Zero-volume bar at the previous close, for an interval without trades
+    fn flat(open_time: u64, close_time: u64, close: Decimal) -> Self {
+        Self {
+            open_time,
+            close_time,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: Decimal::ZERO,
+            trades: 0,
+        }
+    }
+}
+
+/// A bar still receiving trades. Late trades may arrive out of order, so the times of the
+/// trades that set the open and close are kept to tell which one came first
+#[derive(Debug, Clone)]
+struct OpenBar {
+    bar: Bar,
+    first_trade: u64,
+    last_trade: u64,
+}
+
+impl OpenBar {
+    fn new(open_time: u64, close_time: u64, px: Decimal, time: u64) -> Self {
+        Self {
+            bar: Bar {
+                open_time,
+                close_time,
+                open: px,
+                high: px,
+                low: px,
+                close: px,
+                volume: Decimal::ZERO,
+                trades: 0,
+            },
+            first_trade: time,
+            last_trade: time,
+        }
+    }
+
+    fn add(&mut self, px: Decimal, sz: Decimal, time: u64) -> Result<()> {
+        let volume = self.bar.volume.checked_add(sz).ok_or_else(|| {
+            Error::DecimalOverflow(format!("bar volume {} + {sz}", self.bar.volume))
+        })?;
+        let bar = &mut self.bar;
+        if time < self.first_trade {
+            self.first_trade = time;
+            bar.open = px;
+        }
+        if time >= self.last_trade {
+            self.last_trade = time;
+            bar.close = px;
+        }
+        bar.high = bar.high.max(px);
+        bar.low = bar.low.min(px);
+        bar.volume = volume;
+        bar.trades += 1;
+        Ok(())
+    }
+}
+
+/// Builds OHLCV bars for a single coin from `trades` messages.
+///
+/// Time bars close once a trade or [`Self::advance`] moves the clock past their end plus the
+/// grace period; until then, late trades are still added to them. Trades for bars that were
+/// already emitted are dropped and counted in [`Self::late_trades`]. Intervals without trades
+/// after the first bar are emitted as flat, zero-volume bars so the series has no gaps.
+#[derive(Debug, Clone)]
+pub struct BarAggregator {
+    pub coin: String,
+    spec: BarSpec,
+    grace_ms: u64,
+    /// latest trade or clock time seen
+    watermark: u64,
+    /// open time of the first bar not emitted yet
+    next_bar: Option<u64>,
+    last_close: Option<Decimal>,
+    open: BTreeMap<u64, OpenBar>,
+    late_trades: u64,
+}
+
+impl BarAggregator {
+    /// Fails if the bar length, volume or tick count is not positive
+    pub fn new(coin: impl Into<String>, spec: BarSpec) -> Result<Self> {
+        let valid = match spec {
+            BarSpec::Interval(_) => true,
+            BarSpec::Millis(ms) => ms > 0,
+            BarSpec::Volume(volume) => volume.is_sign_positive() && !volume.is_zero(),
+            BarSpec::Ticks(ticks) => ticks > 0,
+        };
+        if !valid {
+            return Err(Error::InvalidConfig(format!(
+                "bar size must be positive, got {spec:?}"
+            )));
+        }
+        Ok(Self {
+            coin: coin.into(),
+            spec,
+            grace_ms: 0,
+            watermark: 0,
+            next_bar: None,
+            last_close: None,
+            open: BTreeMap::new(),
+            late_trades: 0,
+        })
+    }
+
+    /// Keep time bars open for `grace` after their end to take in late trades
+    pub fn with_grace_period(mut self, grace: Duration) -> Self {
+        self.grace_ms = grace.as_millis() as u64;
+        self
+    }
+
+    pub fn spec(&self) -> BarSpec {
+        self.spec
+    }
+
+    /// Number of trades dropped because their bar was already emitted
+    pub fn late_trades(&self) -> u64 {
+        self.late_trades
+    }
+
+    /// The most recent bar still receiving trades
+    pub fn current(&self) -> Option<&Bar> {
+        self.open.values().next_back().map(|open| &open.bar)
+    }
+
+    /// Continue from an existing bar, e.g. the still forming candle of a REST snapshot. It
+    /// keeps receiving trades and is emitted when it closes, so trades already counted in it
+    /// should not be pushed again. Only time bars can be seeded; bars older than the last
+    /// emitted one are ignored
+    pub fn seed(&mut self, bar: Bar) {
+        if !self.spec.is_time() || self.next_bar.is_some_and(|next| next > bar.open_time) {
+            return;
+        }
+        self.watermark = self.watermark.max(bar.open_time);
+        self.open.insert(
+            bar.open_time,
+            OpenBar {
+                first_trade: bar.open_time,
+                last_trade: bar.open_time,
+                bar,
+            },
+        );
+    }
+
+    /// Add a trade and return the bars it closed, oldest first. Trades of other coins are
+    /// ignored. A trade whose size overflows the bar volume is rejected and leaves the bars
+    /// unchanged
+    pub fn push(&mut self, trade: &WsTrade) -> Result<Vec<Bar>> {
+        if trade.coin != self.coin {
+            return Ok(vec![]);
+        }
+        match self.spec {
+            BarSpec::Volume(_) | BarSpec::Ticks(_) => self.push_counted(trade),
+            BarSpec::Interval(_) | BarSpec::Millis(_) => {
+                let open_time = self.spec.open_time(trade.time);
+                if self.next_bar.is_some_and(|next| open_time < next) {
+                    self.late_trades += 1;
+                    return Ok(vec![]);
+                }
+                match self.open.get_mut(&open_time) {
+                    Some(open) => open.add(trade.px, trade.sz, trade.time)?,
+                    None => {
+                        let close_time = self.spec.next_open_time(open_time) - 1;
+                        let mut open = OpenBar::new(open_time, close_time, trade.px, trade.time);
+                        open.add(trade.px, trade.sz, trade.time)?;
+                        self.open.insert(open_time, open);
+                    }
+                }
+                Ok(self.advance(trade.time))
+            }
+        }
+    }
+
+    /// Move the clock to `now` (ms) and return the time bars that closed, e.g. during a quiet
+    /// market without trades
+    pub fn advance(&mut self, now: u64) -> Vec<Bar> {
+        self.watermark = self.watermark.max(now);
+        let mut closed = vec![];
+        if !self.spec.is_time() {
+            return closed;
+        }
+        while let Some(open_time) = self.next_bar.or(self.open.keys().next().copied()) {
+            let next_open_time = self.spec.next_open_time(open_time);
+            if next_open_time + self.grace_ms > self.watermark {
+                break;
+            }
+            let bar = match (self.open.remove(&open_time), self.last_close) {
+                (Some(open), _) => open.bar,
+                (None, Some(close)) => Bar::flat(open_time, next_open_time - 1, close),
+                // nothing traded yet, wait for the first bar
+                (None, None) => break,
+            };
+            self.last_close = Some(bar.close);
+            self.next_bar = Some(next_open_time);
+            closed.push(bar);
+        }
+        closed
+    }
+
+    /// Works on a copy of the open bar, so a trade that overflows leaves it as it was
+    fn push_counted(&mut self, trade: &WsTrade) -> Result<Vec<Bar>> {
+        let overflow =
+            || Error::DecimalOverflow(format!("{} trade of size {}", self.coin, trade.sz));
+        let mut current = self.open.get(&0).cloned();
+        let mut closed = vec![];
+        let mut remaining = trade.sz;
+        loop {
+            let open = current
+                .get_or_insert_with(|| OpenBar::new(trade.time, trade.time, trade.px, trade.time));
+            let take = match self.spec {
+                BarSpec::Volume(volume) => {
+                    remaining.min(volume.checked_sub(open.bar.volume).ok_or_else(overflow)?)
+                }
+                _ => remaining,
+            };
+            open.add(trade.px, take, trade.time)?;
+            open.bar.close_time = trade.time;
+            remaining = remaining.checked_sub(take).ok_or_else(overflow)?;
+
+            let full = match self.spec {
+                BarSpec::Volume(volume) => open.bar.volume >= volume,
+                BarSpec::Ticks(ticks) => open.bar.trades >= ticks,
+                _ => false,
+            };
+            if full && let Some(open) = current.take() {
+                closed.push(open.bar);
+            }
+            if remaining.is_zero() || !full {
+                break;
+            }
+        }
+        match current {
+            Some(open) => self.open.insert(0, open),
+            None => self.open.remove(&0),
+        };
+        Ok(closed)
+    }
+}
+
+/// Builds exchange-style candles for a single coin from `trades` messages.
+///
+/// Closed candles have the same shape as those of `candle_snapshot`, so a REST history can
+/// be extended with locally built candles. See [`BarAggregator`] for how bars close.
+#[derive(Debug, Clone)]
+pub struct CandleAggregator {
+    bars: BarAggregator,
+    interval: CandleInterval,
+}
+
+impl CandleAggregator {
+    pub fn new(coin: impl Into<String>, interval: CandleInterval) -> Result<Self> {
+        Ok(Self {
+            bars: BarAggregator::new(coin, BarSpec::Interval(interval))?,
+            interval,
+        })
+    }
+
+    /// Keep candles open for `grace` after their end to take in late trades
+    pub fn with_grace_period(mut self, grace: Duration) -> Self {
+        self.bars = self.bars.with_grace_period(grace);
+        self
+    }
+
+    pub fn interval(&self) -> CandleInterval {
+        self.interval
+    }
+
+    /// Number of trades dropped because their candle was already emitted
+    pub fn late_trades(&self) -> u64 {
+        self.bars.late_trades()
+    }
+
+    /// The most recent candle still receiving trades
+    pub fn current(&self) -> Option<CandleSnapshot> {
+        let bar = self.bars.current()?.clone();
+        Some(bar.into_candle(self.bars.coin.clone(), self.interval))
+    }
+
+    /// Continue from the latest candle of a REST snapshot, see [`BarAggregator::seed`]
+    pub fn seed(&mut self, candle: &CandleSnapshot) {
+        if candle.interval != self.interval || candle.symbol != self.bars.coin {
+            return;
+        }
+        self.bars.seed(Bar {
+            open_time: candle.time_start,
+            close_time: candle.time_end,
+            open: candle.open,
+            high: candle.high,
+            low: candle.low,
+            close: candle.close,
+            volume: candle.volume,
+            trades: candle.trades,
+        });
+    }
+
+    /// Add a trade and return the candles it closed, oldest first
+    pub fn push(&mut self, trade: &WsTrade) -> Result<Vec<CandleSnapshot>> {
+        let closed = self.bars.push(trade)?;
+        Ok(self.candles(closed))
+    }
+
+    /// Move the clock to `now` (ms) and return the candles that closed
+    pub fn advance(&mut self, now: u64) -> Vec<CandleSnapshot> {
+        let closed = self.bars.advance(now);
+        self.candles(closed)
+    }
+
+    fn candles(&self, bars: Vec<Bar>) -> Vec<CandleSnapshot> {
+        bars.into_iter()
+            .map(|bar| bar.into_candle(self.bars.coin.clone(), self.interval))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    fn trade(time: u64, px: &str, sz: &str) -> WsTrade {
+        WsTrade {
+            coin: "BTC".to_string(),
+            side: "B".to_string(),
+            px: d(px),
+            sz: d(sz),
+            time,
+            hash: None,
+        }
+    }
+
+    const MINUTE: u64 = 60_000;
+
+    #[test]
+    fn test_candles_close_and_fill_gaps() {
+        let mut candles = CandleAggregator::new("BTC", CandleInterval::OneMinute).unwrap();
+        assert!(candles.push(&trade(10_000, "100", "1")).unwrap().is_empty());
+        assert!(
+            candles
+                .push(&trade(20_000, "102", "0.5"))
+                .unwrap()
+                .is_empty()
+        );
+        assert!(candles.push(&trade(30_000, "99", "2")).unwrap().is_empty());
+        assert!(candles.push(&trade(50_000, "101", "1")).unwrap().is_empty());
+        assert_eq!(candles.current().unwrap().close, d("101"));
+
+        // minute 1 has no trades
+        let closed = candles
+            .push(&trade(2 * MINUTE + 5_000, "103", "1"))
+            .unwrap();
+        assert_eq!(closed.len(), 2);
+        let first = &closed[0];
+        assert_eq!((first.time_start, first.time_end), (0, MINUTE - 1));
+        assert_eq!(
+            (first.open, first.high, first.low, first.close),
+            (d("100"), d("102"), d("99"), d("101"))
+        );
+        assert_eq!((first.volume, first.trades), (d("4.5"), 4));
+        assert_eq!(
+            (first.symbol.as_str(), first.interval),
+            ("BTC", CandleInterval::OneMinute)
+        );
+        let flat = &closed[1];
+        assert_eq!((flat.time_start, flat.time_end), (MINUTE, 2 * MINUTE - 1));
+        assert_eq!(
+            (flat.open, flat.close, flat.volume, flat.trades),
+            (d("101"), d("101"), Decimal::ZERO, 0)
+        );
+
+        // a quiet market still closes candles
+        let closed = candles.advance(4 * MINUTE);
+        assert_eq!(closed.len(), 2);
+        assert_eq!(closed[0].close, d("103"));
+        assert_eq!(closed[1].volume, Decimal::ZERO);
+        assert!(candles.current().is_none());
+
+        let mut eth = trade(5 * MINUTE, "2000", "1");
+        eth.coin = "ETH".to_string();
+        assert!(candles.push(&eth).unwrap().is_empty());
+        assert_eq!(candles.advance(10 * MINUTE).len(), 6);
+    }
+
+    #[test]
+    fn test_late_trades_within_grace() {
+        let mut candles = CandleAggregator::new("BTC", CandleInterval::OneMinute)
+            .unwrap()
+            .with_grace_period(Duration::from_secs(2));
+        candles.push(&trade(10_000, "100", "1")).unwrap();
+        candles.push(&trade(59_000, "101", "1")).unwrap();
+        // the next minute started, but the grace period did not pass yet
+        assert!(
+            candles
+                .push(&trade(MINUTE + 1_000, "105", "1"))
+                .unwrap()
+                .is_empty()
+        );
+        assert!(candles.push(&trade(5_000, "98", "1")).unwrap().is_empty());
+
+        let closed = candles.push(&trade(MINUTE + 2_000, "106", "1")).unwrap();
+        assert_eq!(closed.len(), 1);
+        let candle = &closed[0];
+        // the late trade is the earliest one, so it sets the open
+        assert_eq!(
+            (candle.open, candle.high, candle.low, candle.close),
+            (d("98"), d("101"), d("98"), d("101"))
+        );
+        assert_eq!(candle.trades, 3);
+
+        assert!(candles.push(&trade(58_000, "90", "1")).unwrap().is_empty());
+        assert_eq!(candles.late_trades(), 1);
+        assert_eq!(candles.current().unwrap().low, d("105"));
+    }
+
+    #[test]
+    fn test_seed_from_snapshot() {
+        let snapshot: CandleSnapshot = serde_json::from_str(
+            r#"{"t":3600000,"T":3659999,"s":"BTC","i":"1m","o":"29295.0","c":"29258.0","h":"29309.0","l":"29250.0","v":"0.98639","n":189}"#,
+        )
+        .unwrap();
+        let mut candles = CandleAggregator::new("BTC", CandleInterval::OneMinute).unwrap();
+        candles.seed(&snapshot);
+        assert_eq!(candles.current(), Some(snapshot.clone()));
+
+        candles.push(&trade(3_630_000, "29320", "0.01")).unwrap();
+        let closed = candles.push(&trade(3_660_000, "29300", "0.1")).unwrap();
+        let candle = &closed[0];
+        assert_eq!((candle.time_start, candle.time_end), (3_600_000, 3_659_999));
+        assert_eq!(candle.open, d("29295"));
+        assert_eq!(candle.high, d("29320"));
+        assert_eq!(candle.close, d("29320"));
+        assert_eq!((candle.volume, candle.trades), (d("0.99639"), 190));
+
+        // a candle of another interval does not continue this series
+        let mut five = CandleAggregator::new("BTC", CandleInterval::FiveMinutes).unwrap();
+        five.seed(&snapshot);
+        assert!(five.current().is_none());
+    }
+
+    #[test]
+    fn test_sub_minute_bars() {
+        let mut bars = BarAggregator::new("BTC", BarSpec::Millis(10_000)).unwrap();
+        bars.push(&trade(1_000, "100", "1")).unwrap();
+        bars.push(&trade(9_999, "101", "1")).unwrap();
+        let closed = bars.push(&trade(10_000, "102", "1")).unwrap();
+        assert_eq!(
+            closed,
+            vec![Bar {
+                open_time: 0,
+                close_time: 9_999,
+                open: d("100"),
+                high: d("101"),
+                low: d("100"),
+                close: d("101"),
+                volume: d("2"),
+                trades: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_volume_bars_split_trades() {
+        let mut bars = BarAggregator::new("BTC", BarSpec::Volume(d("1"))).unwrap();
+        assert!(bars.push(&trade(1, "100", "0.4")).unwrap().is_empty());
+        let closed = bars.push(&trade(2, "101", "2.1")).unwrap();
+        assert_eq!(closed.len(), 2);
+        assert_eq!(closed[0].volume, d("1"));
+        assert_eq!((closed[0].open, closed[0].close), (d("100"), d("101")));
+        assert_eq!((closed[0].open_time, closed[0].close_time), (1, 2));
+        assert_eq!(closed[1].volume, d("1"));
+        assert_eq!(closed[1].trades, 1);
+
+        let rest = bars.current().unwrap();
+        assert_eq!((rest.volume, rest.open), (d("0.5"), d("101")));
+        // volume bars never close on time
+        assert!(bars.advance(u64::MAX / 2).is_empty());
+    }
+
+    #[test]
+    fn test_tick_bars() {
+        let mut bars = BarAggregator::new("BTC", BarSpec::Ticks(3)).unwrap();
+        let closed: Vec<Bar> = (0..7)
+            .flat_map(|i| bars.push(&trade(i, &(100 + i).to_string(), "1")).unwrap())
+            .collect();
+        assert_eq!(closed.len(), 2);
+        assert_eq!((closed[1].open, closed[1].close), (d("103"), d("105")));
+        assert_eq!((closed[1].open_time, closed[1].close_time), (3, 5));
+        assert_eq!(bars.current().unwrap().trades, 1);
+    }
+
+    #[test]
+    fn test_overflowing_trade_is_rejected() {
+        let huge = i128::MAX.to_string();
+        let mut candles = CandleAggregator::new("BTC", CandleInterval::OneMinute).unwrap();
+        candles.push(&trade(1_000, "100", &huge)).unwrap();
+        assert!(matches!(
+            candles.push(&trade(2_000, "101", "1")),
+            Err(Error::DecimalOverflow(_))
+        ));
+        let current = candles.current().unwrap();
+        assert_eq!((current.close, current.trades), (d("100"), 1));
+
+        let mut bars = BarAggregator::new("BTC", BarSpec::Volume(d("10"))).unwrap();
+        bars.push(&trade(1, "100", "4")).unwrap();
+        // too many digits to add to the bar volume
+        let tiny = trade(2, "101", "0.00000000000000000000000000000000000001");
+        assert!(matches!(bars.push(&tiny), Err(Error::DecimalOverflow(_))));
+        let current = bars.current().unwrap();
+        assert_eq!((current.volume, current.trades), (d("4"), 1));
+    }
+
+    #[test]
+    fn test_rejects_empty_bars() {
+        for spec in [
+            BarSpec::Millis(0),
+            BarSpec::Volume(Decimal::ZERO),
+            BarSpec::Volume(d("-1")),
+            BarSpec::Ticks(0),
+        ] {
+            assert!(matches!(
+                BarAggregator::new("BTC", spec),
+                Err(Error::InvalidConfig(_))
+            ));
+        }
+    }
+}
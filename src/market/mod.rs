@@ -1 +1,4 @@
+pub mod book;
+pub mod candles;
 pub mod models;
+pub mod ws;
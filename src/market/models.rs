@@ -1,7 +1,7 @@
 use alloy::primitives::Address;
 // Market-related model definitions
+use crate::Decimal;
 use serde::{Deserialize, Serialize};
-use serde_with::serde_as;
 
 // Re-export components from the original websocket and info modules related to market data
 
@@ -9,28 +9,58 @@ use serde_with::serde_as;
 #[derive(Debug, Serialize)]
 pub struct WsRequest {
     pub method: Method,
-    pub subscription: Subscription,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subscription: Option<Subscription>,
 }
 
-#[derive(Debug, Serialize)]
+impl WsRequest {
+    pub fn subscribe(subscription: Subscription) -> Self {
+        Self {
+            method: Method::Subscribe,
+            subscription: Some(subscription),
+        }
+    }
+    pub fn unsubscribe(subscription: Subscription) -> Self {
+        Self {
+            method: Method::Unsubscribe,
+            subscription: Some(subscription),
+        }
+    }
+    /// Application-level keepalive, answered by a `pong` channel message
+    pub fn ping() -> Self {
+        Self {
+            method: Method::Ping,
+            subscription: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub enum Method {
     Subscribe,
     Unsubscribe,
+    Ping,
 }
-/*
 
-#[derive(Serialize, Debug, Clone)]
+/// A WebSocket subscription, serialized as the `subscription` object of a request
+#[allow(non_snake_case)]
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
 #[serde(tag = "type", rename_all = "camelCase")]
-pub enum HyperliquidSubscription {
+pub enum Subscription {
     AllMids,
     Notification {
         user: Address,
     },
+    /// Aggregate user information, as shown on the web frontend
+    WebData2 {
+        user: Address,
+    },
     OrderUpdates {
         user: Address,
     },
-    User {
+    /// Fills, funding payments, liquidations and non-user cancels, on the `user` channel
+    UserEvents {
         user: Address,
     },
     L2Book {
@@ -41,7 +71,7 @@ pub enum HyperliquidSubscription {
     },
     Candle {
         coin: String,
-        interval: HyperliquidCandleInterval,
+        interval: CandleInterval,
     },
     UserNonFundingLedgerUpdates {
         user: Address,
@@ -52,52 +82,19 @@ pub enum HyperliquidSubscription {
     UserFills {
         user: Address,
     },
-    Bbo {
-        coin: String,
-    },
-    Liquidation {
+    UserTwapSliceFills {
         user: Address,
     },
-    NonUserCancel {
-        coin: String,
-    },
-    UserFunding {
+    UserTwapHistory {
         user: Address,
     },
-}
- */
-#[allow(non_snake_case)]
-#[derive(Debug, Serialize, Clone)]
-#[serde(tag = "type", rename_all = "camelCase")]
-pub enum Subscription {
-    AllMids,
-    Notification {
-        user: Address,
-    },
-    OrderUpdates {
-        user: Address,
-    },
-    User {
-        user: Address,
-    },
-    L2Book {
-        coin: String,
-    },
-    Trades {
+    ActiveAssetCtx {
         coin: String,
     },
-    Candle {
-        coin: String,
-        interval: CandleInterval,
-    },
-    UserNonFundingLedgerUpdates {
-        user: Address,
-    },
-    UserFundings {
-        user: Address,
-    },
-    UserFills {
+    /// Leverage and maximum trade sizes of a user on a perp
+    ActiveAssetData {
         user: Address,
+        coin: String,
     },
     Bbo {
         coin: String,
@@ -105,10 +102,12 @@ pub enum Subscription {
 }
 
 // Define candle interval enum in market model
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum CandleInterval {
     #[serde(rename = "1m")]
     OneMinute,
+    #[serde(rename = "3m")]
+    ThreeMinutes,
     #[serde(rename = "5m")]
     FiveMinutes,
     #[serde(rename = "15m")]
@@ -117,10 +116,107 @@ pub enum CandleInterval {
     ThirtyMinutes,
     #[serde(rename = "1h")]
     OneHour,
+    #[serde(rename = "2h")]
+    TwoHours,
     #[serde(rename = "4h")]
     FourHours,
+    #[serde(rename = "8h")]
+    EightHours,
+    #[serde(rename = "12h")]
+    TwelveHours,
     #[serde(rename = "1d")]
     OneDay,
+    #[serde(rename = "3d")]
+    ThreeDays,
+    #[serde(rename = "1w")]
+    OneWeek,
+    #[serde(rename = "1M")]
+    OneMonth,
+}
+
+const MINUTE_MS: u64 = 60_000;
+const DAY_MS: u64 = 24 * 60 * MINUTE_MS;
+/// Weekly candles open on Mondays; the Monday before the epoch was three days earlier
+const WEEK_OFFSET_MS: u64 = 3 * DAY_MS;
+
+impl CandleInterval {
+    /// Length of a candle in milliseconds. Months are counted as 30 days; use
+    /// [`Self::open_time`] and [`Self::next_open_time`] for exact boundaries
+    pub fn duration_ms(&self) -> u64 {
+        use CandleInterval::*;
+        match self {
+            OneMinute => MINUTE_MS,
+            ThreeMinutes => 3 * MINUTE_MS,
+            FiveMinutes => 5 * MINUTE_MS,
+            FifteenMinutes => 15 * MINUTE_MS,
+            ThirtyMinutes => 30 * MINUTE_MS,
+            OneHour => 60 * MINUTE_MS,
+            TwoHours => 120 * MINUTE_MS,
+            FourHours => 240 * MINUTE_MS,
+            EightHours => 480 * MINUTE_MS,
+            TwelveHours => 720 * MINUTE_MS,
+            OneDay => DAY_MS,
+            ThreeDays => 3 * DAY_MS,
+            OneWeek => 7 * DAY_MS,
+            OneMonth => 30 * DAY_MS,
+        }
+    }
+
+    /// Open time (ms) of the candle containing `time`
+    pub fn open_time(&self, time: u64) -> u64 {
+        match self {
+            CandleInterval::OneWeek => {
+                let week = self.duration_ms();
+                ((time + WEEK_OFFSET_MS) / week * week).saturating_sub(WEEK_OFFSET_MS)
+            }
+            CandleInterval::OneMonth => {
+                let (year, month, _) = civil_from_days(time / DAY_MS);
+                days_from_civil(year, month, 1) * DAY_MS
+            }
+            _ => time / self.duration_ms() * self.duration_ms(),
+        }
+    }
+
+    /// Open time (ms) of the candle following the one opening at `open_time`
+    pub fn next_open_time(&self, open_time: u64) -> u64 {
+        match self {
+            CandleInterval::OneMonth => {
+                let (year, month, _) = civil_from_days(open_time / DAY_MS);
+                let (year, month) = if month == 12 {
+                    (year + 1, 1)
+                } else {
+                    (year, month + 1)
+                };
+                days_from_civil(year, month, 1) * DAY_MS
+            }
+            _ => self.open_time(open_time) + self.duration_ms(),
+        }
+    }
+}
+
+// proleptic Gregorian calendar conversions for dates from 1970 on, see
+// http://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(year: u64, month: u64, day: u64) -> u64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    (year, month, day)
 }
 
 // WebSocket Response Components for Market Data
@@ -141,6 +237,8 @@ pub enum WsResponse {
     Candle(CandleSnapshot),
     #[serde(rename = "bbo")]
     Bbo(WsBbo),
+    #[serde(rename = "pong")]
+    Pong,
     #[serde(other)]
     Other,
 }
@@ -151,13 +249,10 @@ pub struct SubscriptionResponse {
     pub subscription: serde_json::Value,
 }
 
-#[serde_as]
 #[derive(Debug, Deserialize, Clone)]
 pub struct PriceLevel {
-    #[serde_as(as = "serde_with::DisplayFromStr")]
-    pub px: f64,
-    #[serde_as(as = "serde_with::DisplayFromStr")]
-    pub sz: f64,
+    pub px: Decimal,
+    pub sz: Decimal,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub n: Option<u32>,
 }
@@ -180,8 +275,8 @@ pub struct WsBbo {
 pub struct WsTrade {
     pub coin: String,
     pub side: String,
-    pub px: f64,
-    pub sz: f64,
+    pub px: Decimal,
+    pub sz: Decimal,
     pub time: u64,
     pub hash: Option<String>,
 }
@@ -189,18 +284,182 @@ pub struct WsTrade {
 #[derive(Debug, Deserialize)]
 pub struct WsMid {
     pub coin: String,
-    pub mid: f64,
+    pub mid: Decimal,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct CandleSnapshot {
-    pub symbol: String,
-    pub interval: String,
+    /// Open time in milliseconds
+    #[serde(rename = "t")]
     pub time_start: u64,
+    /// Close time in milliseconds, one less than the next candle's open time
+    #[serde(rename = "T")]
     pub time_end: u64,
-    pub open: f64,
-    pub high: f64,
-    pub low: f64,
-    pub close: f64,
-    pub volume: f64,
+    #[serde(rename = "s")]
+    pub symbol: String,
+    #[serde(rename = "i")]
+    pub interval: CandleInterval,
+    #[serde(rename = "o")]
+    pub open: Decimal,
+    #[serde(rename = "h")]
+    pub high: Decimal,
+    #[serde(rename = "l")]
+    pub low: Decimal,
+    #[serde(rename = "c")]
+    pub close: Decimal,
+    #[serde(rename = "v")]
+    pub volume: Decimal,
+    /// Number of trades
+    #[serde(rename = "n")]
+    pub trades: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscription_wire_format() {
+        let user: Address = "0x5e9ee1089755c3435139848e47e6635505d5a13a"
+            .parse()
+            .unwrap();
+        let coin = || "ETH".to_string();
+        let cases = [
+            (Subscription::AllMids, r#"{"type":"allMids"}"#),
+            (
+                Subscription::Notification { user },
+                r#"{"type":"notification","user":"0x5e9ee1089755c3435139848e47e6635505d5a13a"}"#,
+            ),
+            (
+                Subscription::WebData2 { user },
+                r#"{"type":"webData2","user":"0x5e9ee1089755c3435139848e47e6635505d5a13a"}"#,
+            ),
+            (
+                Subscription::Candle {
+                    coin: coin(),
+                    interval: CandleInterval::OneMinute,
+                },
+                r#"{"type":"candle","coin":"ETH","interval":"1m"}"#,
+            ),
+            (
+                Subscription::L2Book { coin: coin() },
+                r#"{"type":"l2Book","coin":"ETH"}"#,
+            ),
+            (
+                Subscription::Trades { coin: coin() },
+                r#"{"type":"trades","coin":"ETH"}"#,
+            ),
+            (
+                Subscription::OrderUpdates { user },
+                r#"{"type":"orderUpdates","user":"0x5e9ee1089755c3435139848e47e6635505d5a13a"}"#,
+            ),
+            (
+                Subscription::UserEvents { user },
+                r#"{"type":"userEvents","user":"0x5e9ee1089755c3435139848e47e6635505d5a13a"}"#,
+            ),
+            (
+                Subscription::UserFills { user },
+                r#"{"type":"userFills","user":"0x5e9ee1089755c3435139848e47e6635505d5a13a"}"#,
+            ),
+            (
+                Subscription::UserFundings { user },
+                r#"{"type":"userFundings","user":"0x5e9ee1089755c3435139848e47e6635505d5a13a"}"#,
+            ),
+            (
+                Subscription::UserNonFundingLedgerUpdates { user },
+                r#"{"type":"userNonFundingLedgerUpdates","user":"0x5e9ee1089755c3435139848e47e6635505d5a13a"}"#,
+            ),
+            (
+                Subscription::ActiveAssetCtx { coin: coin() },
+                r#"{"type":"activeAssetCtx","coin":"ETH"}"#,
+            ),
+            (
+                Subscription::ActiveAssetData { user, coin: coin() },
+                r#"{"type":"activeAssetData","user":"0x5e9ee1089755c3435139848e47e6635505d5a13a","coin":"ETH"}"#,
+            ),
+            (
+                Subscription::UserTwapSliceFills { user },
+                r#"{"type":"userTwapSliceFills","user":"0x5e9ee1089755c3435139848e47e6635505d5a13a"}"#,
+            ),
+            (
+                Subscription::UserTwapHistory { user },
+                r#"{"type":"userTwapHistory","user":"0x5e9ee1089755c3435139848e47e6635505d5a13a"}"#,
+            ),
+            (
+                Subscription::Bbo { coin: coin() },
+                r#"{"type":"bbo","coin":"ETH"}"#,
+            ),
+        ];
+        for (subscription, json) in cases {
+            assert_eq!(serde_json::to_string(&subscription).unwrap(), json);
+        }
+
+        assert_eq!(
+            serde_json::to_string(&WsRequest::subscribe(Subscription::Trades { coin: coin() }))
+                .unwrap(),
+            r#"{"method":"subscribe","subscription":{"type":"trades","coin":"ETH"}}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&WsRequest::unsubscribe(Subscription::UserEvents { user }))
+                .unwrap(),
+            r#"{"method":"unsubscribe","subscription":{"type":"userEvents","user":"0x5e9ee1089755c3435139848e47e6635505d5a13a"}}"#
+        );
+    }
+
+    #[test]
+    fn test_candle_interval_boundaries() {
+        // 2024-03-15T12:00:00Z, a Friday
+        let time = 1710504000000;
+        assert_eq!(CandleInterval::FourHours.open_time(time + 3_600_000), time);
+        assert_eq!(
+            CandleInterval::OneDay.open_time(time),
+            time - 12 * 3_600_000
+        );
+        assert_eq!(
+            CandleInterval::OneHour.next_open_time(time),
+            time + 3_600_000
+        );
+        // weeks open on Monday 2024-03-11
+        assert_eq!(CandleInterval::OneWeek.open_time(time), 1710115200000);
+        assert_eq!(
+            CandleInterval::OneWeek.next_open_time(1710115200000),
+            1710115200000 + 7 * DAY_MS
+        );
+        // months open on the 1st, whatever their length
+        assert_eq!(CandleInterval::OneMonth.open_time(time), 1709251200000);
+        assert_eq!(
+            CandleInterval::OneMonth.next_open_time(1709251200000),
+            1711929600000
+        );
+        // 2024-12-31T23:59:00Z rolls over into 2025
+        assert_eq!(
+            CandleInterval::OneMonth.open_time(1735689540000),
+            1733011200000
+        );
+        assert_eq!(
+            CandleInterval::OneMonth.next_open_time(1733011200000),
+            1735689600000
+        );
+    }
+
+    #[test]
+    fn test_candle_interval_wire_names() {
+        let names = [
+            (CandleInterval::ThreeMinutes, "3m"),
+            (CandleInterval::TwoHours, "2h"),
+            (CandleInterval::EightHours, "8h"),
+            (CandleInterval::TwelveHours, "12h"),
+            (CandleInterval::ThreeDays, "3d"),
+            (CandleInterval::OneWeek, "1w"),
+            (CandleInterval::OneMonth, "1M"),
+        ];
+        for (interval, name) in names {
+            let json = format!("\"{name}\"");
+            assert_eq!(serde_json::to_string(&interval).unwrap(), json);
+            assert_eq!(
+                serde_json::from_str::<CandleInterval>(&json).unwrap(),
+                interval
+            );
+        }
+    }
 }
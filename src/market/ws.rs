@@ -0,0 +1,897 @@
+use crate::HyperliquidUrls;
+use crate::error::{Error, Result};
+use crate::execution::models::HyperliquidChain;
+use crate::market::models::{Method, Subscription, SubscriptionResponse, WsRequest, WsResponse};
+use futures::{SinkExt, Stream, StreamExt};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use tracing::{debug, warn};
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+#[derive(Debug, Clone)]
+pub struct WsOptions {
+    /// How long to wait for a `subscriptionResponse` before giving up
+    pub ack_timeout: Duration,
+    /// The connection is considered dead when nothing arrives for this long
+    pub heartbeat_timeout: Duration,
+    /// Reconnect backoff starts here and doubles on each failed attempt
+    pub min_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Give up after this many consecutive failed attempts. `None` retries forever
+    pub max_reconnect_attempts: Option<u32>,
+    /// Send `{"method":"ping"}` this often. Hyperliquid closes sockets that stay quiet for 60s
+    pub ping_interval: Option<Duration>,
+    /// The connection is considered dead if a ping goes unanswered for this long
+    pub pong_timeout: Duration,
+}
+
+impl Default for WsOptions {
+    fn default() -> Self {
+        Self {
+            ack_timeout: Duration::from_secs(10),
+            heartbeat_timeout: Duration::from_secs(60),
+            min_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            max_reconnect_attempts: None,
+            ping_interval: Some(Duration::from_secs(50)),
+            pong_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum WsEvent<M = WsResponse> {
+    Message(M),
+    /// The connection dropped and was re-established with every active subscription replayed.
+    /// Messages may have been missed in the last `gap_ms` milliseconds, so local state should be resynced.
+    Reconnected {
+        gap_ms: u64,
+    },
+}
+
+/// WebSocket client for market data subscriptions.
+///
+/// The socket is driven by a background task; subscribe/unsubscribe calls wait for the
+/// `subscriptionResponse` acknowledgement, and all other messages are yielded as a [`Stream`].
+/// Dropped or silent connections are re-established with backoff and active subscriptions are replayed.
+///
+/// Channel messages are decoded as `M`, [`WsResponse`] unless connected with
+/// [`Self::connect_typed`].
+pub struct HyperliquidWsClient<M = WsResponse> {
+    commands: mpsc::UnboundedSender<Command>,
+    messages: mpsc::UnboundedReceiver<Result<WsEvent<M>>>,
+    ack_timeout: Duration,
+}
+
+struct Command {
+    method: Method,
+    subscription: Subscription,
+    ack: oneshot::Sender<Result<()>>,
+}
+
+struct PendingAck {
+    method: Method,
+    subscription: Subscription,
+    expected: Value,
+    /// `None` for subscriptions replayed after a reconnect
+    ack: Option<oneshot::Sender<Result<()>>>,
+}
+
+impl HyperliquidWsClient {
+    pub async fn connect(chain: HyperliquidChain) -> Result<Self> {
+        let config = HyperliquidUrls::from_chain(chain);
+
+        Self::connect_with_config(&config).await
+    }
+    pub async fn connect_with_config(config: &HyperliquidUrls) -> Result<Self> {
+        Self::connect_with_options(config, WsOptions::default()).await
+    }
+    pub async fn connect_with_options(
+        config: &HyperliquidUrls,
+        options: WsOptions,
+    ) -> Result<Self> {
+        Self::connect_typed(config, options).await
+    }
+}
+
+impl<M: DeserializeOwned + Send + 'static> HyperliquidWsClient<M> {
+    /// Connect and decode channel messages as `M` instead of [`WsResponse`]
+    pub async fn connect_typed(config: &HyperliquidUrls, options: WsOptions) -> Result<Self> {
+        let url = config.ws_endpoint.clone();
+        let (socket, _) = tokio_tungstenite::connect_async(url.as_str()).await?;
+        let (commands_tx, commands_rx) = mpsc::unbounded_channel();
+        let (messages_tx, messages_rx) = mpsc::unbounded_channel();
+        let ack_timeout = options.ack_timeout;
+        let connection = Connection {
+            url,
+            options,
+            commands: commands_rx,
+            messages: messages_tx,
+            active: vec![],
+            pending: vec![],
+            last_message: Instant::now(),
+            ping_sent: None,
+        };
+        tokio::spawn(connection.run(socket));
+        Ok(Self {
+            commands: commands_tx,
+            messages: messages_rx,
+            ack_timeout,
+        })
+    }
+
+    pub async fn subscribe(&self, subscription: Subscription) -> Result<()> {
+        self.request(Method::Subscribe, subscription).await
+    }
+
+    pub async fn unsubscribe(&self, subscription: Subscription) -> Result<()> {
+        self.request(Method::Unsubscribe, subscription).await
+    }
+
+    async fn request(&self, method: Method, subscription: Subscription) -> Result<()> {
+        let (ack_tx, ack_rx) = oneshot::channel();
+        self.commands
+            .send(Command {
+                method,
+                subscription: subscription.clone(),
+                ack: ack_tx,
+            })
+            .map_err(|_| Error::NotConnected)?;
+        match tokio::time::timeout(self.ack_timeout, ack_rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(Error::NotConnected),
+            Err(_) => Err(Error::MissingSubscriptionResponse(subscription)),
+        }
+    }
+}
+
+impl<M> Stream for HyperliquidWsClient<M> {
+    type Item = Result<WsEvent<M>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.messages.poll_recv(cx)
+    }
+}
+
+enum Disconnect {
+    ClientGone,
+    Lost,
+}
+
+/// State owned by the background task
+struct Connection<M> {
+    url: String,
+    options: WsOptions,
+    commands: mpsc::UnboundedReceiver<Command>,
+    messages: mpsc::UnboundedSender<Result<WsEvent<M>>>,
+    /// Subscriptions to replay after a reconnect, with their serialized form
+    active: Vec<(Value, Subscription)>,
+    pending: Vec<PendingAck>,
+    last_message: Instant,
+    /// When the outstanding ping was sent, if any
+    ping_sent: Option<Instant>,
+}
+
+impl<M: DeserializeOwned> Connection<M> {
+    async fn run(mut self, mut socket: WsStream) {
+        loop {
+            if let Disconnect::ClientGone = self.session(&mut socket).await {
+                let _ = socket.close(None).await;
+                return;
+            }
+            let Some(new_socket) = self.reconnect().await else {
+                return;
+            };
+            socket = new_socket;
+            let gap_ms = self.last_message.elapsed().as_millis() as u64;
+            self.last_message = Instant::now();
+            if self
+                .messages
+                .send(Ok(WsEvent::Reconnected { gap_ms }))
+                .is_err()
+            {
+                let _ = socket.close(None).await;
+                return;
+            }
+        }
+    }
+
+    async fn session(&mut self, socket: &mut WsStream) -> Disconnect {
+        let mut last_ping = Instant::now();
+        self.ping_sent = None;
+        loop {
+            let heartbeat =
+                tokio::time::sleep_until(self.last_message + self.options.heartbeat_timeout);
+            let ping_interval = self.options.ping_interval.unwrap_or_default();
+            let ping = tokio::time::sleep_until(last_ping + ping_interval);
+            let pong_deadline = tokio::time::sleep_until(
+                self.ping_sent.unwrap_or(last_ping) + self.options.pong_timeout,
+            );
+            tokio::select! {
+                command = self.commands.recv() => {
+                    let Some(command) = command else {
+                        return Disconnect::ClientGone;
+                    };
+                    if let Err(err) = self.send_command(socket, command).await {
+                        warn!("ws send failed: {}", err);
+                        return Disconnect::Lost;
+                    }
+                }
+                message = socket.next() => {
+                    match message {
+                        Some(Ok(Message::Text(text))) => {
+                            self.last_message = Instant::now();
+                            if !self.handle_text(&text) {
+                                return Disconnect::ClientGone;
+                            }
+                        }
+                        Some(Ok(Message::Close(frame))) => {
+                            debug!("ws closed by server: {:?}", frame);
+                            return Disconnect::Lost;
+                        }
+                        Some(Ok(_)) => self.last_message = Instant::now(),
+                        Some(Err(err)) => {
+                            warn!("ws error: {}", err);
+                            return Disconnect::Lost;
+                        }
+                        None => return Disconnect::Lost,
+                    }
+                }
+                _ = heartbeat => {
+                    warn!(
+                        "no ws message for {:?}, reconnecting",
+                        self.options.heartbeat_timeout
+                    );
+                    return Disconnect::Lost;
+                }
+                _ = ping, if self.options.ping_interval.is_some() && self.ping_sent.is_none() => {
+                    last_ping = Instant::now();
+                    self.ping_sent = Some(last_ping);
+                    let text = serde_json::to_string(&WsRequest::ping()).unwrap_or_default();
+                    if let Err(err) = socket.send(Message::text(text)).await {
+                        warn!("ws ping failed: {}", err);
+                        return Disconnect::Lost;
+                    }
+                }
+                _ = pong_deadline, if self.ping_sent.is_some() => {
+                    warn!(
+                        "no ws pong within {:?}, reconnecting",
+                        self.options.pong_timeout
+                    );
+                    return Disconnect::Lost;
+                }
+            }
+        }
+    }
+
+    async fn send_command(&mut self, socket: &mut WsStream, command: Command) -> Result<()> {
+        let request = WsRequest {
+            method: command.method,
+            subscription: Some(command.subscription.clone()),
+        };
+        let (text, expected) = match serde_json::to_string(&request)
+            .and_then(|text| Ok((text, serde_json::to_value(&command.subscription)?)))
+        {
+            Ok(encoded) => encoded,
+            Err(err) => {
+                let _ = command.ack.send(Err(err.into()));
+                return Ok(());
+            }
+        };
+        match command.method {
+            Method::Subscribe => {
+                if !self.active.iter().any(|(v, _)| *v == expected) {
+                    self.active
+                        .push((expected.clone(), command.subscription.clone()));
+                }
+            }
+            Method::Unsubscribe => self.active.retain(|(v, _)| *v != expected),
+            Method::Ping => {}
+        }
+        self.pending.push(PendingAck {
+            method: command.method,
+            subscription: command.subscription,
+            expected,
+            ack: Some(command.ack),
+        });
+        debug!("ws send {}", text);
+        socket.send(Message::text(text)).await?;
+        Ok(())
+    }
+
+    /// Returns false once the client is gone
+    fn handle_text(&mut self, text: &str) -> bool {
+        self.pending
+            .retain(|p| p.ack.as_ref().is_none_or(|ack| !ack.is_closed()));
+        let value = match serde_json::from_str::<Value>(text) {
+            Ok(value) => value,
+            Err(err) => {
+                warn!("failed to decode ws message {}: {}", text, err);
+                return self.messages.send(Err(err.into())).is_ok();
+            }
+        };
+        // control messages are handled here; everything else is decoded as `M`
+        match value.get("channel").and_then(Value::as_str) {
+            Some("pong") => {
+                self.ping_sent = None;
+                return true;
+            }
+            Some("subscriptionResponse") => {
+                if let Some(ack) = value
+                    .get("data")
+                    .and_then(|data| SubscriptionResponse::deserialize(data).ok())
+                    && let Some(i) = self.pending.iter().position(|p| p.matches(&ack))
+                    && let Some(ack) = self.pending.remove(i).ack
+                {
+                    let _ = ack.send(Ok(()));
+                }
+                return true;
+            }
+            Some("error") => {
+                let error = value
+                    .get("data")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                if let Some(i) = self
+                    .pending
+                    .iter()
+                    .position(|p| error_mentions(error, &p.expected))
+                {
+                    let p = self.pending.remove(i);
+                    warn!("subscription {:?} failed: {}", p.subscription, error);
+                    if let Method::Subscribe = p.method {
+                        self.active.retain(|(v, _)| *v != p.expected);
+                    }
+                    let err = Error::SubscriptionFailed(p.subscription);
+                    return match p.ack {
+                        Some(ack) => {
+                            let _ = ack.send(Err(err));
+                            true
+                        }
+                        None => self.messages.send(Err(err)).is_ok(),
+                    };
+                }
+                // not tied to a pending request, surfaced as a message below
+                warn!("ws error: {}", error);
+            }
+            _ => {}
+        }
+        match M::deserialize(value) {
+            Ok(message) => self.messages.send(Ok(WsEvent::Message(message))).is_ok(),
+            Err(err) => {
+                warn!("failed to decode ws message {}: {}", text, err);
+                self.messages.send(Err(err.into())).is_ok()
+            }
+        }
+    }
+
+    async fn reconnect(&mut self) -> Option<WsStream> {
+        let mut attempt = 0;
+        loop {
+            if self.messages.is_closed() {
+                return None;
+            }
+            if self
+                .options
+                .max_reconnect_attempts
+                .is_some_and(|max| attempt >= max)
+            {
+                let _ = self.messages.send(Err(Error::NotConnected));
+                return None;
+            }
+            tokio::time::sleep(self.backoff(attempt)).await;
+            attempt += 1;
+            let mut socket = match tokio_tungstenite::connect_async(self.url.as_str()).await {
+                Ok((socket, _)) => socket,
+                Err(err) => {
+                    warn!("ws reconnect attempt {} failed: {}", attempt, err);
+                    continue;
+                }
+            };
+            match self.replay(&mut socket).await {
+                Ok(()) => return Some(socket),
+                Err(err) => warn!("ws replay after reconnect failed: {}", err),
+            }
+        }
+    }
+
+    /// Resubscribe everything that was active. Callers still waiting on a subscribe keep their
+    /// acknowledgement; pending unsubscribes are complete since the old session is gone.
+    async fn replay(&mut self, socket: &mut WsStream) -> Result<()> {
+        let mut waiting = vec![];
+        for p in std::mem::take(&mut self.pending) {
+            match p.method {
+                Method::Subscribe => waiting.push(p),
+                Method::Unsubscribe | Method::Ping => {
+                    if let Some(ack) = p.ack {
+                        let _ = ack.send(Ok(()));
+                    }
+                }
+            }
+        }
+        for (expected, subscription) in self.active.clone() {
+            let ack = waiting
+                .iter_mut()
+                .find(|p| p.expected == expected)
+                .and_then(|p| p.ack.take());
+            self.pending.push(PendingAck {
+                method: Method::Subscribe,
+                subscription: subscription.clone(),
+                expected,
+                ack,
+            });
+            let request = WsRequest::subscribe(subscription);
+            socket
+                .send(Message::text(serde_json::to_string(&request)?))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Exponential backoff with jitter in `[base / 2, base]`
+    fn backoff(&self, attempt: u32) -> Duration {
+        let base = self
+            .options
+            .min_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.options.max_backoff);
+        base.mul_f64(rand::random_range(0.5..=1.0))
+    }
+}
+
+impl PendingAck {
+    fn matches(&self, ack: &SubscriptionResponse) -> bool {
+        let method = match self.method {
+            Method::Subscribe => "subscribe",
+            Method::Unsubscribe => "unsubscribe",
+            Method::Ping => "ping",
+        };
+        ack.method == method && value_contains(&ack.subscription, &self.expected)
+    }
+}
+
+/// Whether `actual` carries every field of `expected`. The server echoes addresses in lower
+/// case and may add optional fields, so strings compare case-insensitively and extra keys are ignored.
+fn value_contains(actual: &Value, expected: &Value) -> bool {
+    match (actual, expected) {
+        (Value::Object(actual), Value::Object(expected)) => expected
+            .iter()
+            .all(|(k, v)| actual.get(k).is_some_and(|a| value_contains(a, v))),
+        (Value::String(actual), Value::String(expected)) => actual.eq_ignore_ascii_case(expected),
+        (actual, expected) => actual == expected,
+    }
+}
+
+/// Error messages embed the offending subscription as JSON, e.g. `Invalid subscription {...}`
+fn error_mentions(error: &str, expected: &Value) -> bool {
+    error
+        .find('{')
+        .and_then(|i| serde_json::from_str::<Value>(&error[i..]).ok())
+        .is_some_and(|v| value_contains(&v, expected))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::net::TcpListener;
+
+    /// Bind a local WebSocket server; `handler` is invoked for each accepted connection.
+    pub async fn serve<F, Fut>(handler: F) -> HyperliquidUrls
+    where
+        F: Fn(WebSocketStream<TcpStream>) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let Ok(socket) = tokio_tungstenite::accept_async(stream).await else {
+                    continue;
+                };
+                tokio::spawn(handler(socket));
+            }
+        });
+        let mut config = HyperliquidUrls::from_chain(HyperliquidChain::Dev);
+        config.set_ws_endpoint(format!("ws://{}/ws", addr));
+        config
+    }
+
+    pub async fn recv_json(socket: &mut WebSocketStream<TcpStream>) -> Value {
+        loop {
+            match socket.next().await.unwrap().unwrap() {
+                Message::Text(text) => return serde_json::from_str(&text).unwrap(),
+                _ => continue,
+            }
+        }
+    }
+
+    pub async fn send_json(socket: &mut WebSocketStream<TcpStream>, value: Value) {
+        socket.send(Message::text(value.to_string())).await.unwrap();
+    }
+
+    /// Acknowledge a subscribe/unsubscribe request by echoing it back
+    pub async fn ack(socket: &mut WebSocketStream<TcpStream>) -> Value {
+        let request = recv_json(socket).await;
+        send_json(
+            socket,
+            serde_json::json!({"channel": "subscriptionResponse", "data": request}),
+        )
+        .await;
+        request
+    }
+
+    fn btc_book() -> Subscription {
+        Subscription::L2Book {
+            coin: "BTC".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_and_stream() -> eyre::Result<()> {
+        let config = serve(|mut socket| async move {
+            let request = recv_json(&mut socket).await;
+            assert_eq!(
+                request,
+                serde_json::json!({"method": "subscribe", "subscription": {"type": "l2Book", "coin": "BTC"}})
+            );
+            // the server adds optional fields to the echoed subscription
+            send_json(
+                &mut socket,
+                serde_json::json!({"channel": "subscriptionResponse", "data": {"method": "subscribe", "subscription": {"type": "l2Book", "coin": "BTC", "nSigFigs": null}}}),
+            )
+            .await;
+            send_json(
+                &mut socket,
+                serde_json::json!({"channel": "l2Book", "data": {"coin": "BTC", "time": 1700000000000u64, "levels": [[{"px": "65000.0", "sz": "1.5", "n": 3}], [{"px": "65001.0", "sz": "0.5", "n": 1}]]}}),
+            )
+            .await;
+            ack(&mut socket).await;
+            let _ = socket.next().await;
+        })
+        .await;
+
+        let mut client = HyperliquidWsClient::connect_with_config(&config).await?;
+        client.subscribe(btc_book()).await?;
+        match client.next().await.unwrap()? {
+            WsEvent::Message(WsResponse::L2Book(book)) => {
+                assert_eq!(book.coin, "BTC");
+                assert_eq!(book.levels.0[0].px, "65000.0".parse()?);
+                assert_eq!(book.levels.1[0].sz, "0.5".parse()?);
+            }
+            other => panic!("unexpected message: {other:?}"),
+        }
+        client.unsubscribe(btc_book()).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_subscription_failed() -> eyre::Result<()> {
+        let config = serve(|mut socket| async move {
+            recv_json(&mut socket).await;
+            send_json(
+                &mut socket,
+                serde_json::json!({"channel": "error", "data": "Invalid subscription {\"type\":\"l2Book\",\"coin\":\"BTC\"}"}),
+            )
+            .await;
+            let _ = socket.next().await;
+        })
+        .await;
+
+        let client = HyperliquidWsClient::connect_with_config(&config).await?;
+        let result = client.subscribe(btc_book()).await;
+        assert!(
+            matches!(result, Err(Error::SubscriptionFailed(Subscription::L2Book { ref coin })) if coin == "BTC"),
+            "{result:?}"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unrelated_error_keeps_pending_subscription() -> eyre::Result<()> {
+        let config = serve(|mut socket| async move {
+            let request = recv_json(&mut socket).await;
+            send_json(
+                &mut socket,
+                serde_json::json!({"channel": "error", "data": "Invalid subscription {\"type\":\"trades\",\"coin\":\"ETH\"}"}),
+            )
+            .await;
+            send_json(
+                &mut socket,
+                serde_json::json!({"channel": "subscriptionResponse", "data": request}),
+            )
+            .await;
+            let _ = socket.next().await;
+        })
+        .await;
+
+        let mut client = HyperliquidWsClient::connect_with_config(&config).await?;
+        client.subscribe(btc_book()).await?;
+        match client.next().await.unwrap()? {
+            WsEvent::Message(WsResponse::Error(error)) => assert!(error.contains("trades")),
+            other => panic!("unexpected {other:?}"),
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_missing_subscription_response() -> eyre::Result<()> {
+        let config = serve(|mut socket| async move {
+            recv_json(&mut socket).await;
+            let _ = socket.next().await;
+        })
+        .await;
+
+        let options = WsOptions {
+            ack_timeout: Duration::from_millis(100),
+            ..WsOptions::default()
+        };
+        let client = HyperliquidWsClient::connect_with_options(&config, options).await?;
+        let result = client.subscribe(btc_book()).await;
+        assert!(
+            matches!(result, Err(Error::MissingSubscriptionResponse(_))),
+            "{result:?}"
+        );
+        Ok(())
+    }
+
+    fn fast_reconnect() -> WsOptions {
+        WsOptions {
+            ack_timeout: Duration::from_secs(1),
+            heartbeat_timeout: Duration::from_secs(5),
+            min_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(50),
+            max_reconnect_attempts: Some(20),
+            ping_interval: None,
+            pong_timeout: Duration::from_secs(1),
+        }
+    }
+
+    fn book(coin: &str, time: u64) -> Value {
+        serde_json::json!({"channel": "l2Book", "data": {"coin": coin, "time": time, "levels": [[], []]}})
+    }
+
+    async fn next_book_time(client: &mut HyperliquidWsClient) -> u64 {
+        match client.next().await.unwrap().unwrap() {
+            WsEvent::Message(WsResponse::L2Book(book)) => book.time,
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_ends_when_reconnect_disabled() -> eyre::Result<()> {
+        let config = serve(|mut socket| async move {
+            ack(&mut socket).await;
+            let _ = socket.close(None).await;
+        })
+        .await;
+
+        let options = WsOptions {
+            max_reconnect_attempts: Some(0),
+            ..WsOptions::default()
+        };
+        let mut client = HyperliquidWsClient::connect_with_options(&config, options).await?;
+        client.subscribe(btc_book()).await?;
+        assert!(matches!(
+            client.next().await,
+            Some(Err(Error::NotConnected))
+        ));
+        assert!(client.next().await.is_none());
+        let result = client.subscribe(btc_book()).await;
+        assert!(matches!(result, Err(Error::NotConnected)), "{result:?}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_replays_subscriptions() -> eyre::Result<()> {
+        let connections = Arc::new(AtomicUsize::new(0));
+        let counter = connections.clone();
+        let config = serve(move |mut socket| {
+            let connection = counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                // every connection must see both subscriptions, in order
+                let first = ack(&mut socket).await;
+                let second = ack(&mut socket).await;
+                assert_eq!(first["subscription"]["coin"], "BTC");
+                assert_eq!(second["subscription"]["coin"], "ETH");
+                send_json(&mut socket, book("BTC", connection as u64)).await;
+                if connection == 0 {
+                    // kill the connection mid-stream without a close frame
+                    drop(socket);
+                } else {
+                    let _ = socket.next().await;
+                }
+            }
+        })
+        .await;
+
+        let mut client =
+            HyperliquidWsClient::connect_with_options(&config, fast_reconnect()).await?;
+        client
+            .subscribe(Subscription::Trades {
+                coin: "BTC".to_string(),
+            })
+            .await?;
+        client
+            .subscribe(Subscription::Trades {
+                coin: "ETH".to_string(),
+            })
+            .await?;
+        assert_eq!(next_book_time(&mut client).await, 0);
+        match client.next().await.unwrap()? {
+            WsEvent::Reconnected { gap_ms } => assert!(gap_ms < 5_000, "{gap_ms}"),
+            other => panic!("unexpected event: {other:?}"),
+        }
+        // replayed acknowledgements are not surfaced
+        assert_eq!(next_book_time(&mut client).await, 1);
+        assert_eq!(connections.load(Ordering::SeqCst), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_after_heartbeat_timeout() -> eyre::Result<()> {
+        let connections = Arc::new(AtomicUsize::new(0));
+        let counter = connections.clone();
+        let config = serve(move |mut socket| {
+            let connection = counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                ack(&mut socket).await;
+                if connection == 0 {
+                    // stay open but go silent
+                    tokio::time::sleep(Duration::from_secs(30)).await;
+                } else {
+                    send_json(&mut socket, book("BTC", 7)).await;
+                    let _ = socket.next().await;
+                }
+            }
+        })
+        .await;
+
+        let options = WsOptions {
+            heartbeat_timeout: Duration::from_millis(200),
+            ..fast_reconnect()
+        };
+        let mut client = HyperliquidWsClient::connect_with_options(&config, options).await?;
+        client
+            .subscribe(Subscription::Trades {
+                coin: "BTC".to_string(),
+            })
+            .await?;
+        match client.next().await.unwrap()? {
+            WsEvent::Reconnected { gap_ms } => assert!(gap_ms >= 200, "{gap_ms}"),
+            other => panic!("unexpected event: {other:?}"),
+        }
+        assert_eq!(next_book_time(&mut client).await, 7);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unsubscribed_is_not_replayed() -> eyre::Result<()> {
+        let connections = Arc::new(AtomicUsize::new(0));
+        let counter = connections.clone();
+        let config = serve(move |mut socket| {
+            let connection = counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if connection == 0 {
+                    ack(&mut socket).await;
+                    ack(&mut socket).await;
+                    let unsubscribed = ack(&mut socket).await;
+                    assert_eq!(unsubscribed["method"], "unsubscribe");
+                    drop(socket);
+                } else {
+                    let replayed = ack(&mut socket).await;
+                    assert_eq!(replayed["subscription"]["coin"], "ETH");
+                    send_json(&mut socket, book("ETH", 3)).await;
+                    let _ = socket.next().await;
+                }
+            }
+        })
+        .await;
+
+        let mut client =
+            HyperliquidWsClient::connect_with_options(&config, fast_reconnect()).await?;
+        client.subscribe(btc_book()).await?;
+        client
+            .subscribe(Subscription::Trades {
+                coin: "ETH".to_string(),
+            })
+            .await?;
+        client.unsubscribe(btc_book()).await?;
+        assert!(matches!(
+            client.next().await.unwrap()?,
+            WsEvent::Reconnected { .. }
+        ));
+        assert_eq!(next_book_time(&mut client).await, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ping_pong_messages() -> eyre::Result<()> {
+        assert_eq!(
+            serde_json::to_string(&WsRequest::ping())?,
+            r#"{"method":"ping"}"#
+        );
+        let pong: WsResponse = serde_json::from_str(r#"{"channel":"pong"}"#)?;
+        assert!(matches!(pong, WsResponse::Pong));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_keepalive_pings_are_answered() -> eyre::Result<()> {
+        let pings = Arc::new(AtomicUsize::new(0));
+        let counter = pings.clone();
+        let config = serve(move |mut socket| {
+            let pings = counter.clone();
+            async move {
+                ack(&mut socket).await;
+                loop {
+                    let request = recv_json(&mut socket).await;
+                    assert_eq!(request, serde_json::json!({"method": "ping"}));
+                    if pings.fetch_add(1, Ordering::SeqCst) == 2 {
+                        send_json(&mut socket, book("BTC", 42)).await;
+                    }
+                    send_json(&mut socket, serde_json::json!({"channel": "pong"})).await;
+                }
+            }
+        })
+        .await;
+
+        let options = WsOptions {
+            ping_interval: Some(Duration::from_millis(50)),
+            pong_timeout: Duration::from_millis(200),
+            ..fast_reconnect()
+        };
+        let mut client = HyperliquidWsClient::connect_with_options(&config, options).await?;
+        client.subscribe(btc_book()).await?;
+        // pongs are consumed internally and the connection is never recycled
+        assert_eq!(next_book_time(&mut client).await, 42);
+        assert!(pings.load(Ordering::SeqCst) >= 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_missing_pong_reconnects() -> eyre::Result<()> {
+        let connections = Arc::new(AtomicUsize::new(0));
+        let counter = connections.clone();
+        let config = serve(move |mut socket| {
+            let connection = counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                ack(&mut socket).await;
+                if connection == 0 {
+                    // swallow pings without answering
+                    while socket.next().await.is_some() {}
+                } else {
+                    send_json(&mut socket, book("BTC", 9)).await;
+                    let _ = socket.next().await;
+                }
+            }
+        })
+        .await;
+
+        let options = WsOptions {
+            ping_interval: Some(Duration::from_millis(50)),
+            pong_timeout: Duration::from_millis(100),
+            ..fast_reconnect()
+        };
+        let mut client = HyperliquidWsClient::connect_with_options(&config, options).await?;
+        client.subscribe(btc_book()).await?;
+        assert!(matches!(
+            client.next().await.unwrap()?,
+            WsEvent::Reconnected { .. }
+        ));
+        assert_eq!(next_book_time(&mut client).await, 9);
+        Ok(())
+    }
+}
@@ -1,15 +1,356 @@
+use crate::Decimal;
+use crate::HyperliquidUrls;
+use crate::error::{Error, Result};
+use crate::execution::bracket::{BracketOrder, validate_bracket};
+use crate::execution::models::{
+    Action, ApproveAgent, CancelRequest, Grouping, HyperliquidChain, HyperliquidOrderRequest,
+    HyperliquidRequest, ModifyRequest, OkResponse, OrderId, RequestCancelByClientId, Response,
+    ResponseData, SpotSend, Status, UsdClassTransfer, UsdSend, Withdraw3,
+};
+use crate::execution::nonce::NonceManager;
+use crate::rest::helper::HyperliquidRestClientHelper;
+use crate::rest::models::API;
+use crate::sign::{UserSignedAction, sign_l1_action, sign_user_signed_action};
+use alloy::primitives::Address;
+use alloy::signers::local::PrivateKeySigner;
+
+/// Endpoint to sign and submit actions on behalf of a wallet.
+pub struct HyperliquidExchangeClient {
+    pub client: HyperliquidRestClientHelper,
+    pub chain: HyperliquidChain,
+    wallet: PrivateKeySigner,
+    vault_address: Option<Address>,
+    nonces: NonceManager,
+}
+
+impl HyperliquidExchangeClient {
+    pub fn new(
+        chain: HyperliquidChain,
+        wallet: PrivateKeySigner,
+        vault_address: Option<Address>,
+    ) -> Self {
+        let config = HyperliquidUrls::from_chain(chain);
+
+        Self::new_with_config(chain, &config, wallet, vault_address)
+    }
+    pub fn new_with_config(
+        chain: HyperliquidChain,
+        config: &HyperliquidUrls,
+        wallet: PrivateKeySigner,
+        vault_address: Option<Address>,
+    ) -> Self {
+        Self {
+            client: HyperliquidRestClientHelper::new(config.rest_endpoint.clone()),
+            chain,
+            wallet,
+            vault_address,
+            nonces: NonceManager::new(),
+        }
+    }
+    /// Draw nonces from a shared manager, for clients signing with the same key
+    pub fn with_nonce_manager(mut self, nonces: NonceManager) -> Self {
+        self.nonces = nonces;
+        self
+    }
+    pub fn nonce_manager(&self) -> &NonceManager {
+        &self.nonces
+    }
+    /// Address of the signing wallet
+    pub fn address(&self) -> Address {
+        self.wallet.address()
+    }
+    pub fn wallet(&self) -> &PrivateKeySigner {
+        &self.wallet
+    }
+    pub fn vault_address(&self) -> Option<Address> {
+        self.vault_address
+    }
+
+    /// Trade for `vault_address` (a vault or sub-account) instead of the wallet's own account
+    pub fn with_vault_address(mut self, vault_address: Option<Address>) -> Self {
+        self.vault_address = vault_address;
+        self
+    }
+    /// A client signing with the same wallet and nonces, bound to another vault or sub-account
+    pub fn for_vault(&self, vault_address: Option<Address>) -> Self {
+        Self {
+            client: self.client.clone(),
+            chain: self.chain,
+            wallet: self.wallet.clone(),
+            vault_address,
+            nonces: self.nonces.clone(),
+        }
+    }
+
+    /// Sign an L1 action and submit it to the exchange endpoint, on behalf of the bound vault
+    pub async fn post_action(&self, action: Action) -> Result<Response> {
+        self.post_action_for(action, self.vault_address).await
+    }
+    async fn post_action_for(
+        &self,
+        action: Action,
+        vault_address: Option<Address>,
+    ) -> Result<Response> {
+        let nonce = self.nonces.next()?;
+        let connection_id = action.hash(nonce, vault_address.unwrap_or_default())?;
+        let signature = sign_l1_action(self.chain, &self.wallet, connection_id).await?;
+        let request = HyperliquidRequest {
+            action,
+            nonce,
+            signature,
+            vault_address,
+        };
+        self.client.post(API::Exchange, &request).await
+    }
+
+    /// Sign a user-signed action with the wallet itself and submit it. Unlike L1 actions these
+    /// are never sent on behalf of a vault
+    async fn post_user_signed<A: UserSignedAction>(
+        &self,
+        payload: A,
+        into_action: fn(A) -> Action,
+    ) -> Result<Response> {
+        let signature = sign_user_signed_action(&self.wallet, &payload).await?;
+        let request = HyperliquidRequest {
+            nonce: payload.nonce(),
+            action: into_action(payload),
+            signature,
+            vault_address: None,
+        };
+        self.client.post(API::Exchange, &request).await
+    }
+
+    /// Place a batch of orders. Returns one status per order, in request order
+    pub async fn place_orders(
+        &self,
+        orders: Vec<HyperliquidOrderRequest>,
+        grouping: Grouping,
+    ) -> Result<Vec<Status>> {
+        validate_bracket(&orders, grouping)?;
+        let response = self.post_action(Action::Order { orders, grouping }).await?;
+        response.into_statuses()
+    }
+
+    /// Place an entry with its take-profit and stop-loss, or TP/SL on an existing position
+    pub async fn place_bracket(&self, bracket: BracketOrder) -> Result<Vec<Status>> {
+        self.place_orders(bracket.orders, bracket.grouping).await
+    }
+
+    /// Cancel orders by order id
+    pub async fn cancel(&self, cancels: Vec<CancelRequest>) -> Result<Vec<Status>> {
+        let response = self.post_action(Action::Cancel { cancels }).await?;
+        response.into_statuses()
+    }
+
+    /// Cancel orders by client order id
+    pub async fn cancel_by_cloid(
+        &self,
+        cancels: Vec<RequestCancelByClientId>,
+    ) -> Result<Vec<Status>> {
+        let response = self.post_action(Action::CancelByCloid { cancels }).await?;
+        response.into_statuses()
+    }
+
+    /// Amend a resting order in place
+    pub async fn modify(
+        &self,
+        oid: impl Into<OrderId>,
+        order: HyperliquidOrderRequest,
+    ) -> Result<Vec<Status>> {
+        let response = self
+            .post_action(Action::Modify {
+                oid: oid.into(),
+                order,
+            })
+            .await?;
+        response.into_statuses()
+    }
+
+    /// Amend several resting orders atomically. Returns one status per modify, in request order
+    pub async fn batch_modify(&self, modifies: Vec<ModifyRequest>) -> Result<Vec<Status>> {
+        let response = self.post_action(Action::BatchModify { modifies }).await?;
+        response.into_statuses()
+    }
+
+    /// Schedule a cancel of all open orders at `time` (ms), or clear the schedule with `None`.
+    /// The time must be at least 5 seconds in the future
+    pub async fn schedule_cancel(&self, time: Option<u64>) -> Result<()> {
+        let response = self.post_action(Action::ScheduleCancel { time }).await?;
+        response.into_ok().map(|_| ())
+    }
+
+    /// Send USDC to another address on Hyperliquid
+    pub async fn usd_send(&self, destination: Address, amount: Decimal) -> Result<()> {
+        let payload = UsdSend::new(self.chain, destination, amount, self.nonces.next()?);
+        let response = self.post_user_signed(payload, Action::UsdSend).await?;
+        response.into_ok().map(|_| ())
+    }
+
+    /// Withdraw USDC to `destination` on Arbitrum
+    pub async fn withdraw(&self, destination: Address, amount: Decimal) -> Result<()> {
+        let payload = Withdraw3::new(self.chain, destination, amount, self.nonces.next()?);
+        let response = self.post_user_signed(payload, Action::Withdraw3).await?;
+        response.into_ok().map(|_| ())
+    }
+
+    /// Send a spot token, identified as `NAME:tokenId`, to another address
+    pub async fn spot_send(
+        &self,
+        destination: Address,
+        token: impl Into<String>,
+        amount: Decimal,
+    ) -> Result<()> {
+        let payload = SpotSend::new(self.chain, destination, token, amount, self.nonces.next()?);
+        let response = self.post_user_signed(payload, Action::SpotSend).await?;
+        response.into_ok().map(|_| ())
+    }
+
+    /// Move USDC from spot to perp (`to_perp`) or back
+    pub async fn usd_class_transfer(&self, amount: Decimal, to_perp: bool) -> Result<()> {
+        let payload = UsdClassTransfer::new(self.chain, amount, to_perp, self.nonces.next()?);
+        let response = self
+            .post_user_signed(payload, Action::UsdClassTransfer)
+            .await?;
+        response.into_ok().map(|_| ())
+    }
+
+    /// Authorize `agent_address` as an API wallet of this account. Approving a new unnamed agent
+    /// replaces the previous unnamed one; named agents stay valid until replaced by name
+    pub async fn approve_agent(
+        &self,
+        agent_address: Address,
+        agent_name: Option<String>,
+    ) -> Result<()> {
+        let payload = ApproveAgent::new(self.chain, agent_address, agent_name, self.nonces.next()?);
+        let response = self.post_user_signed(payload, Action::ApproveAgent).await?;
+        response.into_ok().map(|_| ())
+    }
+
+    /// Generate a fresh API wallet, approve it from this (master) wallet and return a client
+    /// signing with it. The agent trades for the same account and vault; persist its key via
+    /// [`Self::wallet`] to reuse it across restarts
+    pub async fn create_agent(&self, agent_name: Option<String>) -> Result<Self> {
+        let agent = PrivateKeySigner::random();
+        self.approve_agent(agent.address(), agent_name).await?;
+        Ok(Self {
+            client: self.client.clone(),
+            chain: self.chain,
+            wallet: agent,
+            vault_address: self.vault_address,
+            // nonces are tracked per signer
+            nonces: NonceManager::new(),
+        })
+    }
+
+    /// Create a sub-account of this (master) wallet and return its address
+    pub async fn create_sub_account(&self, name: impl Into<String>) -> Result<Address> {
+        let action = Action::CreateSubAccount { name: name.into() };
+        let ok = self.post_action_for(action, None).await?.into_ok()?;
+        match ok.data {
+            Some(ResponseData::Other(serde_json::Value::String(address))) => address
+                .parse()
+                .map_err(|_| Error::ExchangeError(format!("invalid sub-account: {address}"))),
+            data => Err(Error::ExchangeError(format!(
+                "unexpected createSubAccount response: {data:?}"
+            ))),
+        }
+    }
+
+    /// Move USDC from the master account to a sub-account (`is_deposit`) or back
+    pub async fn sub_account_transfer(
+        &self,
+        sub_account_user: Address,
+        is_deposit: bool,
+        usd: Decimal,
+    ) -> Result<()> {
+        let action = Action::SubAccountTransfer {
+            sub_account_user,
+            is_deposit,
+            usd: usd_to_micros(usd)?,
+        };
+        self.post_action_for(action, None)
+            .await?
+            .into_ok()
+            .map(|_| ())
+    }
+
+    /// Deposit USDC into a vault (`is_deposit`) or withdraw from it
+    pub async fn vault_transfer(
+        &self,
+        vault_address: Address,
+        is_deposit: bool,
+        usd: Decimal,
+    ) -> Result<()> {
+        let action = Action::VaultTransfer {
+            vault_address,
+            is_deposit,
+            usd: usd_to_micros(usd)?,
+        };
+        self.post_action_for(action, None)
+            .await?
+            .into_ok()
+            .map(|_| ())
+    }
+
+    /// Update cross or isolated leverage of an asset
+    pub async fn update_leverage(
+        &self,
+        asset: u32,
+        is_cross: bool,
+        leverage: u32,
+    ) -> Result<OkResponse> {
+        let response = self
+            .post_action(Action::UpdateLeverage {
+                asset,
+                is_cross,
+                leverage,
+            })
+            .await?;
+        response.into_ok()
+    }
+
+    /// Add or remove margin from an isolated position. `ntli` is in USD with 6 decimals
+    pub async fn update_isolated_margin(
+        &self,
+        asset: u32,
+        is_buy: bool,
+        ntli: i64,
+    ) -> Result<OkResponse> {
+        let response = self
+            .post_action(Action::UpdateIsolatedMargin {
+                asset,
+                is_buy,
+                ntli,
+            })
+            .await?;
+        response.into_ok()
+    }
+}
+
+/// Transfers between accounts are denominated in micro USDC
+fn usd_to_micros(usd: Decimal) -> Result<u64> {
+    usd.with_scale(6)
+        .and_then(|usd| u64::try_from(usd.mantissa()).ok())
+        .ok_or_else(|| Error::InvalidAmount(format!("{usd} is not a valid USDC amount")))
+}
+
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
+    use super::{HyperliquidExchangeClient, usd_to_micros};
+    use crate::error::Error;
     use crate::execution::models::agent::agent_sol;
     use crate::execution::models::{
-        Action, CancelRequest, Grouping, HyperliquidChain, HyperliquidOrderRequest,
-        HyperliquidOrderType, HyperliquidTif, RequestCancelByClientId, TpSl,
+        Action, ApproveAgent, CancelRequest, Grouping, HyperliquidChain, HyperliquidOrderRequest,
+        HyperliquidOrderType, HyperliquidTif, ModifyRequest, OrderId, RequestCancelByClientId,
+        Response, Status, TpSl, UsdSend,
     };
-    use crate::sign::tests::get_wallet;
-    use crate::sign::{HyperliquidSignature, L1_DOMAIN, sign_l1_action};
+    use crate::rest::helper::tests::{serve_once, serve_sequence};
+    use crate::sign::tests::{get_wallet, reference_wallet};
+    use crate::sign::{HyperliquidSignature, L1_DOMAIN, UserSignedAction, sign_l1_action};
     use crate::utils::uuid_to_hex_string;
+    use crate::{Decimal, HyperliquidUrls};
     use alloy::dyn_abi::Eip712Domain;
-    use alloy::primitives::{Address, B256, keccak256};
+    use alloy::primitives::{Address, B256, PrimitiveSignature, U256, keccak256};
     use alloy::signers::Signer;
     use alloy::sol_types::{SolStruct, SolValue};
     use malachite::base::strings::{ToDebugString, ToLowerHexString};
@@ -22,8 +363,8 @@ mod tests {
             orders: vec![HyperliquidOrderRequest {
                 asset: 1,
                 is_buy: true,
-                limit_px: "2000.0".to_string(),
-                sz: "3.5".to_string(),
+                limit_px: "2000.0".parse()?,
+                sz: "3.5".parse()?,
                 reduce_only: false,
                 order_type: HyperliquidOrderType::Limit {
                     tif: HyperliquidTif::Ioc,
@@ -34,30 +375,61 @@ mod tests {
         };
         assert_eq!(
             serde_json::to_string(&action)?,
-            r#"{"type":"order","orders":[{"a":1,"b":true,"p":"2000.0","s":"3.5","r":false,"t":{"limit":{"tif":"Ioc"}}}],"grouping":"na"}"#
+            r#"{"type":"order","orders":[{"a":1,"b":true,"p":"2000","s":"3.5","r":false,"t":{"limit":{"tif":"Ioc"}}}],"grouping":"na"}"#
         );
         let connection_id = action.hash(1583838, Address::new([0; 20]))?;
         assert_eq!(
             connection_id.to_debug_string(),
-            "0x5983a9453b8d32668daefa9310e1a81bc1f4d7da50a9ad8869a4011d12068ea0"
+            "0xaca0f0820256154c457b4b50ff0ae1c7f09df8b978d10b308d7cf912479cf916"
         );
 
         let signature = sign_l1_action(HyperliquidChain::Arbitrum, &wallet, connection_id).await?;
         assert_eq!(
             hex::encode(signature.as_primitive_signature().as_bytes()),
-            "77957e58e70f43b6b68581f2dc42011fc384538a2e5b7bf42d5b936f19fbb67360721a8598727230f67080efee48c812a6a4442013fd3b0eed509171bef9f23f1c"
+            "f99bde2cfc90712d0f154d76dc44d1b93b6cdf7ae2b10303030a37f4bdaaad4c73b773c43c925baf427c387231131624a071fc23935a6cb1dc99aa1e9710a8ec1c"
         );
 
         let signature =
             sign_l1_action(HyperliquidChain::ArbitrumGoerli, &wallet, connection_id).await?;
         assert_eq!(
             hex::encode(signature.as_primitive_signature().as_bytes()),
-            "cd0925372ff1ed499e54883e9a6205ecfadec748f80ec463fe2f84f1209648776377961965cb7b12414186b1ea291e95fd512722427efcbcfb3b0b2bcd4d79d01c"
+            "2b5aab4ffbe6eb62661eb38296a6295b606330230207f2512d54f20ed550488926878533a02fde683adfc4c952ea9f376ffe4c1013d1db7bc29e6f5b487eaefb1b"
         );
 
         Ok(())
     }
 
+    #[test]
+    fn test_order_hash_ignores_trailing_zeros() -> eyre::Result<()> {
+        let action = |px: Decimal, sz: Decimal| Action::Order {
+            orders: vec![HyperliquidOrderRequest {
+                asset: 1,
+                is_buy: false,
+                limit_px: px,
+                sz,
+                reduce_only: true,
+                order_type: HyperliquidOrderType::Trigger {
+                    is_market: true,
+                    trigger_px: px,
+                    tpsl: TpSl::Sl,
+                },
+                cloid: None,
+            }],
+            grouping: Grouping::Na,
+        };
+        let canonical = action("2000".parse()?, "8.37".parse()?).hash(1583838, Address::ZERO)?;
+        assert_eq!(
+            action("2000.0".parse()?, "8.3700".parse()?).hash(1583838, Address::ZERO)?,
+            canonical
+        );
+        let sz: Decimal = "2700".parse::<Decimal>()? * "0.0031".parse::<Decimal>()?;
+        assert_eq!(
+            action("2000.000".parse()?, sz).hash(1583838, Address::ZERO)?,
+            canonical
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_limit_order_action_hashing_with_cloid() -> eyre::Result<()> {
         let cloid = uuid::Uuid::from_str("1e60610f-0b3d-4205-97c8-8c1fed2ad5ee")?;
@@ -66,8 +438,8 @@ mod tests {
             orders: vec![HyperliquidOrderRequest {
                 asset: 1,
                 is_buy: true,
-                limit_px: "2000.0".to_string(),
-                sz: "3.5".to_string(),
+                limit_px: "2000.0".parse()?,
+                sz: "3.5".parse()?,
                 reduce_only: false,
                 order_type: HyperliquidOrderType::Limit {
                     tif: HyperliquidTif::Ioc,
@@ -81,14 +453,14 @@ mod tests {
         let signature = sign_l1_action(HyperliquidChain::Arbitrum, &wallet, connection_id).await?;
         assert_eq!(
             signature.to_string(),
-            "d3e894092eb27098077145714630a77bbe3836120ee29df7d935d8510b03a08f456de5ec1be82aa65fc6ecda9ef928b0445e212517a98858cfaa251c4cd7552b1c"
+            "1b62128584e3b0ced14b8a4982616efaef380faac8f207292fea18082281c69f76e636fb2118bebbaf1a75e851f4d8b7ce824b064b42333228cc0ce1312d55141c"
         );
 
         let signature =
             sign_l1_action(HyperliquidChain::ArbitrumGoerli, &wallet, connection_id).await?;
         assert_eq!(
             signature.to_string(),
-            "3768349dbb22a7fd770fc9fc50c7b5124a7da342ea579b309f58002ceae49b4357badc7909770919c45d850aabb08474ff2b7b3204ae5b66d9f7375582981f111c"
+            "416f1d5a98d4a4e587f13d2911ea7334d92f7073e013216206629e6a0afeb1506b7cf18657a75e3aa674b74b224f0415ff57abc590b07ddb8647244b20730e601b"
         );
 
         Ok(())
@@ -128,6 +500,151 @@ mod tests {
         );
         Ok(())
     }
+    /// Order of the signing tests in the official Python SDK: 100 ETH at 100, asset 1
+    pub(crate) fn sdk_order(order_type: HyperliquidOrderType) -> HyperliquidOrderRequest {
+        HyperliquidOrderRequest {
+            asset: 1,
+            is_buy: true,
+            limit_px: "100".parse().unwrap(),
+            sz: "100".parse().unwrap(),
+            reduce_only: false,
+            order_type,
+            cloid: None,
+        }
+    }
+
+    /// MessagePack map with its keys in the given order, as `msgpack.packb` writes a Python dict
+    pub(crate) fn packb(entries: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let mut bytes = vec![0x80 | entries.len() as u8];
+        for (key, value) in entries {
+            bytes.extend(rmp_serde::to_vec(key).unwrap());
+            bytes.extend(value);
+        }
+        bytes
+    }
+
+    #[tokio::test]
+    async fn test_order_signing_matches_python_sdk() -> eyre::Result<()> {
+        // `test_l1_action_signing_order_matches` in the Python SDK's tests/signing_test.py
+        let wallet = reference_wallet();
+        let action = Action::Order {
+            orders: vec![sdk_order(HyperliquidOrderType::Limit {
+                tif: HyperliquidTif::Gtc,
+            })],
+            grouping: Grouping::Na,
+        };
+        let connection_id = action.hash(0, Address::ZERO)?;
+
+        let mainnet = sign_l1_action(HyperliquidChain::Arbitrum, &wallet, connection_id).await?;
+        assert_eq!(
+            mainnet.r.to_lower_hex_string(),
+            "d65369825a9df5d80099e513cce430311d7d26ddf477f5b3a33d2806b100d78e"
+        );
+        assert_eq!(
+            mainnet.s.to_lower_hex_string(),
+            "2b54116ff64054968aa237c20ca9ff68000f977c93289157748a3162b6ea940e"
+        );
+        assert_eq!(mainnet.v, 28);
+
+        let testnet =
+            sign_l1_action(HyperliquidChain::ArbitrumGoerli, &wallet, connection_id).await?;
+        assert_eq!(
+            testnet.r.to_lower_hex_string(),
+            "82b2ba28e76b3d761093aaded1b1cdad4960b3af30212b343fb2e6cdfa4e3d54"
+        );
+        assert_eq!(
+            testnet.s.to_lower_hex_string(),
+            "6b53878fc99d26047f4d7e8c90eb98955a109f44209163f52d8dc4278cbbd9f5"
+        );
+        assert_eq!(testnet.v, 27);
+        Ok(())
+    }
+
+    // The SDKs ship no modify vectors. Modify actions hash the same way as the order checked
+    // above, so these compare the bytes against the dicts the official SDK and the API docs
+    // build, key by key, around that order's wire.
+
+    #[test]
+    fn test_modify_wire() -> eyre::Result<()> {
+        let order = sdk_order(HyperliquidOrderType::Limit {
+            tif: HyperliquidTif::Gtc,
+        });
+        let action = Action::Modify {
+            oid: OrderId::Oid(123),
+            order: order.clone(),
+        };
+        assert_eq!(
+            serde_json::to_string(&action)?,
+            r#"{"type":"modify","oid":123,"order":{"a":1,"b":true,"p":"100","s":"100","r":false,"t":{"limit":{"tif":"Gtc"}}}}"#
+        );
+        // {"type": "modify", "oid": 123, "order": order_wire} from the exchange endpoint docs
+        assert_eq!(
+            rmp_serde::to_vec_named(&action)?,
+            packb(&[
+                ("type", rmp_serde::to_vec("modify")?),
+                ("oid", rmp_serde::to_vec(&123u64)?),
+                ("order", rmp_serde::to_vec_named(&order)?),
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_batch_modify_wire() -> eyre::Result<()> {
+        let cloid = "0x9c09a42dede9495ea86bb4bc3888cc2d".to_string();
+        let first = sdk_order(HyperliquidOrderType::Limit {
+            tif: HyperliquidTif::Gtc,
+        });
+        let second = HyperliquidOrderRequest {
+            is_buy: false,
+            limit_px: "2800.5".parse()?,
+            sz: "0.01".parse()?,
+            reduce_only: true,
+            order_type: HyperliquidOrderType::Limit {
+                tif: HyperliquidTif::Alo,
+            },
+            cloid: Some(cloid.clone()),
+            ..first.clone()
+        };
+        let action = Action::BatchModify {
+            modifies: vec![
+                ModifyRequest {
+                    oid: 123.into(),
+                    order: first.clone(),
+                },
+                ModifyRequest {
+                    oid: OrderId::Cloid(cloid.clone()),
+                    order: second.clone(),
+                },
+            ],
+        };
+        assert_eq!(
+            serde_json::to_string(&action)?,
+            r#"{"type":"batchModify","modifies":[{"oid":123,"order":{"a":1,"b":true,"p":"100","s":"100","r":false,"t":{"limit":{"tif":"Gtc"}}}},{"oid":"0x9c09a42dede9495ea86bb4bc3888cc2d","order":{"a":1,"b":false,"p":"2800.5","s":"0.01","r":true,"t":{"limit":{"tif":"Alo"}},"c":"0x9c09a42dede9495ea86bb4bc3888cc2d"}}]}"#
+        );
+        // {"type": "batchModify", "modifies": [{"oid": oid, "order": order_wire}]} as built by
+        // `bulk_modify_orders_new` in the Python SDK, with cloids as raw hex strings
+        let modify = |oid: Vec<u8>, order: &HyperliquidOrderRequest| -> eyre::Result<Vec<u8>> {
+            Ok(packb(&[
+                ("oid", oid),
+                ("order", rmp_serde::to_vec_named(order)?),
+            ]))
+        };
+        let modifies = [
+            vec![0x92],
+            modify(rmp_serde::to_vec(&123u64)?, &first)?,
+            modify(rmp_serde::to_vec(&cloid)?, &second)?,
+        ]
+        .concat();
+        assert_eq!(
+            rmp_serde::to_vec_named(&action)?,
+            packb(&[
+                ("type", rmp_serde::to_vec("batchModify")?),
+                ("modifies", modifies),
+            ])
+        );
+        Ok(())
+    }
     #[tokio::test]
     async fn test_sign_actual_order_trigger() -> eyre::Result<()> {
         // {"action":{"type":"order","orders":[{"a":13,"b":true,"p":"16","s":"0.5","r":false,"t":{"trigger":{"isMarket":false,"tpsl":"sl","triggerPx":"16"}},"c":"0x172f684508034185932d9165bca4bc0b"}],"grouping":"na"},"nonce":1741110304133,"signature":{"r":"0xa105bcd3054eb61c0e8c8b26601133568d441e2891737cf30ed1547b3c99fe18","s":"0x1ea54db7953408299f595ca7e4963f57b53aa8b43d095cc8a67b1eead7ab48a","v":27}}
@@ -136,13 +653,13 @@ mod tests {
             orders: vec![HyperliquidOrderRequest {
                 asset: 13,
                 is_buy: true,
-                limit_px: "16".to_string(),
-                sz: "0.5".to_string(),
+                limit_px: "16".parse()?,
+                sz: "0.5".parse()?,
                 reduce_only: false,
                 order_type: HyperliquidOrderType::Trigger {
                     is_market: false,
                     tpsl: TpSl::Sl,
-                    trigger_px: "16".to_string(),
+                    trigger_px: "16".parse()?,
                 },
                 cloid: Some("0x172f684508034185932d9165bca4bc0b".to_string()),
             }],
@@ -175,8 +692,8 @@ mod tests {
             orders: vec![HyperliquidOrderRequest {
                 asset: 1,
                 is_buy: true,
-                limit_px: "2700".to_string(),
-                sz: "0.0031".to_string(),
+                limit_px: "2700".parse()?,
+                sz: "0.0031".parse()?,
                 reduce_only: false,
                 order_type: HyperliquidOrderType::Limit {
                     tif: HyperliquidTif::Gtc,
@@ -207,8 +724,8 @@ mod tests {
             orders: vec![HyperliquidOrderRequest {
                 asset: 1,
                 is_buy: true,
-                limit_px: "2700".to_string(),
-                sz: "0.0031".to_string(),
+                limit_px: "2700".parse()?,
+                sz: "0.0031".parse()?,
                 reduce_only: false,
                 order_type: HyperliquidOrderType::Limit {
                     tif: HyperliquidTif::Gtc,
@@ -261,4 +778,352 @@ mod tests {
 
         Ok(())
     }
+
+    /// Address recovered from the signature of a posted request
+    fn recover_signer(body: &serde_json::Value, signing_hash: B256) -> eyre::Result<Address> {
+        let signature = PrimitiveSignature::new(
+            U256::from_str(body["signature"]["r"].as_str().unwrap())?,
+            U256::from_str(body["signature"]["s"].as_str().unwrap())?,
+            body["signature"]["v"].as_u64().unwrap() != 27,
+        );
+        Ok(signature.recover_address_from_prehash(&signing_hash)?)
+    }
+
+    pub(crate) fn local_client(host: String) -> HyperliquidExchangeClient {
+        let mut config = HyperliquidUrls::from_chain(HyperliquidChain::Arbitrum);
+        config.set_rest_endpoint(host);
+        HyperliquidExchangeClient::new_with_config(
+            HyperliquidChain::Arbitrum,
+            &config,
+            get_wallet(),
+            None,
+        )
+    }
+    fn limit_order() -> HyperliquidOrderRequest {
+        HyperliquidOrderRequest {
+            asset: 1,
+            is_buy: true,
+            limit_px: Decimal::new(2700, 0),
+            sz: Decimal::new(31, 4),
+            reduce_only: false,
+            order_type: HyperliquidOrderType::Limit {
+                tif: HyperliquidTif::Gtc,
+            },
+            cloid: None,
+        }
+    }
+
+    #[test]
+    fn test_response_decoding() -> eyre::Result<()> {
+        let response: Response = serde_json::from_str(
+            r#"{"status":"ok","response":{"type":"order","data":{"statuses":[{"resting":{"oid":77738308}},{"filled":{"totalSz":"0.02","avgPx":"1891.4","oid":77747314}},{"error":"Order must have minimum value of $10."},"waitingForFill"]}}}"#,
+        )?;
+        let statuses = response.into_statuses()?;
+        assert!(matches!(&statuses[0], Status::Resting(r) if r.oid == 77738308));
+        assert!(
+            matches!(&statuses[1], Status::Filled(f) if f.oid == 77747314 && f.total_sz.to_string() == "0.02" && f.avg_px.to_string() == "1891.4")
+        );
+        assert!(
+            matches!(&statuses[2], Status::Error(e) if e == "Order must have minimum value of $10.")
+        );
+        assert!(matches!(&statuses[3], Status::WaitingForFill));
+
+        let response: Response = serde_json::from_str(
+            r#"{"status":"ok","response":{"type":"cancel","data":{"statuses":["success","somethingNew"]}}}"#,
+        )?;
+        let statuses = response.into_statuses()?;
+        assert!(matches!(statuses[0], Status::Success));
+        assert!(matches!(statuses[1], Status::Other));
+
+        let response: Response =
+            serde_json::from_str(r#"{"status":"ok","response":{"type":"default"}}"#)?;
+        assert_eq!(response.into_ok()?.response_type, "default");
+
+        let response: Response = serde_json::from_str(
+            r#"{"status":"err","response":"User or API Wallet does not exist."}"#,
+        )?;
+        assert!(
+            matches!(response.into_ok(), Err(Error::ExchangeError(e)) if e == "User or API Wallet does not exist.")
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_place_orders_signs_and_posts() -> eyre::Result<()> {
+        let (host, server) = serve_once(
+            200,
+            r#"{"status":"ok","response":{"type":"order","data":{"statuses":[{"resting":{"oid":77738308}}]}}}"#,
+        )
+        .await;
+        let client = local_client(host);
+        let statuses = client
+            .place_orders(vec![limit_order()], Grouping::Na)
+            .await?;
+        assert!(matches!(&statuses[0], Status::Resting(r) if r.oid == 77738308));
+
+        let captured = server.await?;
+        assert!(captured.head.starts_with("POST /exchange HTTP/1.1"));
+        let body: serde_json::Value = serde_json::from_str(&captured.body)?;
+        assert_eq!(
+            body["action"],
+            serde_json::to_value(Action::Order {
+                orders: vec![limit_order()],
+                grouping: Grouping::Na,
+            })?
+        );
+        assert!(body.get("vaultAddress").is_none());
+
+        // the signature must recover to the client's wallet for the posted nonce
+        let nonce = body["nonce"].as_u64().unwrap();
+        let connection_id = Action::Order {
+            orders: vec![limit_order()],
+            grouping: Grouping::Na,
+        }
+        .hash(nonce, Address::ZERO)?;
+        let signing_hash = agent_sol::Agent {
+            source: "a".to_string(),
+            connectionId: connection_id,
+        }
+        .eip712_signing_hash(L1_DOMAIN);
+        assert_eq!(recover_signer(&body, signing_hash)?, client.address());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_schedule_cancel() -> eyre::Result<()> {
+        let action = Action::ScheduleCancel {
+            time: Some(1741146714088),
+        };
+        assert_eq!(
+            serde_json::to_string(&action)?,
+            r#"{"type":"scheduleCancel","time":1741146714088}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&Action::ScheduleCancel { time: None })?,
+            r#"{"type":"scheduleCancel"}"#
+        );
+        assert_eq!(
+            hex::encode(rmp_serde::to_vec_named(&action)?),
+            "82a474797065ae7363686564756c6543616e63656ca474696d65cf00000195646e6fe8"
+        );
+        let connection_id = action.hash(1741146700000, Address::new([0; 20]))?;
+        assert_eq!(
+            connection_id.to_debug_string(),
+            "0x35ab364bc61a951d3164f8d8b4675fe7862fb96184e5b1b073d938e3e83c0a0a"
+        );
+
+        let (host, server) =
+            serve_once(200, r#"{"status":"ok","response":{"type":"default"}}"#).await;
+        local_client(host)
+            .schedule_cancel(Some(1741146714088))
+            .await?;
+        let body: serde_json::Value = serde_json::from_str(&server.await?.body)?;
+        assert_eq!(body["action"]["time"], 1741146714088u64);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_update_leverage_and_margin() -> eyre::Result<()> {
+        let (host, mut requests) = serve_sequence(vec![
+            r#"{"status":"ok","response":{"type":"default"}}"#.to_string(),
+            r#"{"status":"err","response":"Insufficient margin to add"}"#.to_string(),
+        ])
+        .await;
+        let client = local_client(host);
+        let ok = client.update_leverage(4, false, 10).await?;
+        assert_eq!(ok.response_type, "default");
+        let body: serde_json::Value = serde_json::from_str(&requests.recv().await.unwrap().body)?;
+        assert_eq!(
+            body["action"],
+            serde_json::json!({"type": "updateLeverage", "asset": 4, "isCross": false, "leverage": 10})
+        );
+
+        let result = client.update_isolated_margin(4, true, 1_000_000).await;
+        assert!(
+            matches!(result, Err(Error::ExchangeError(e)) if e == "Insufficient margin to add")
+        );
+        let body: serde_json::Value = serde_json::from_str(&requests.recv().await.unwrap().body)?;
+        assert_eq!(
+            body["action"],
+            serde_json::json!({"type": "updateIsolatedMargin", "asset": 4, "isBuy": true, "ntli": 1000000})
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_usd_send_is_user_signed() -> eyre::Result<()> {
+        let (host, server) =
+            serve_once(200, r#"{"status":"ok","response":{"type":"default"}}"#).await;
+        let client = local_client(host);
+        let destination: Address = "0x5e9ee1089755c3435139848e47e6635505d5a13a".parse()?;
+        client.usd_send(destination, "12.5".parse()?).await?;
+
+        let body: serde_json::Value = serde_json::from_str(&server.await?.body)?;
+        let action = &body["action"];
+        assert_eq!(action["type"], "usdSend");
+        assert_eq!(action["hyperliquidChain"], "Mainnet");
+        assert_eq!(
+            action["destination"],
+            "0x5e9ee1089755c3435139848e47e6635505d5a13a"
+        );
+        assert_eq!(action["amount"], "12.5");
+        assert_eq!(action["time"], body["nonce"]);
+        assert!(body.get("vaultAddress").is_none());
+
+        let payload = UsdSend::new(
+            HyperliquidChain::Arbitrum,
+            destination,
+            "12.5".parse()?,
+            body["nonce"].as_u64().unwrap(),
+        );
+        let signer = recover_signer(&body, payload.eip712_signing_hash())?;
+        assert_eq!(signer, client.address());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_create_agent() -> eyre::Result<()> {
+        let (host, server) =
+            serve_once(200, r#"{"status":"ok","response":{"type":"default"}}"#).await;
+        let master = local_client(host);
+        let agent = master.create_agent(Some("mm-week-42".to_string())).await?;
+        assert_ne!(agent.address(), master.address());
+        assert_eq!(agent.vault_address(), master.vault_address());
+
+        let body: serde_json::Value = serde_json::from_str(&server.await?.body)?;
+        let action = &body["action"];
+        assert_eq!(action["type"], "approveAgent");
+        assert_eq!(action["agentName"], "mm-week-42");
+        assert_eq!(action["nonce"], body["nonce"]);
+        assert_eq!(
+            action["agentAddress"]
+                .as_str()
+                .unwrap()
+                .parse::<Address>()?,
+            agent.address()
+        );
+
+        // approved by the master key
+        let payload = ApproveAgent::new(
+            HyperliquidChain::Arbitrum,
+            agent.address(),
+            Some("mm-week-42".to_string()),
+            body["nonce"].as_u64().unwrap(),
+        );
+        let signer = recover_signer(&body, payload.eip712_signing_hash())?;
+        assert_eq!(signer, master.address());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sub_account_and_vault_transfer_conn_id() -> eyre::Result<()> {
+        let action = Action::SubAccountTransfer {
+            sub_account_user: "0x5e9ee1089755c3435139848e47e6635505d5a13a".parse()?,
+            is_deposit: true,
+            usd: usd_to_micros("1".parse()?)?,
+        };
+        assert_eq!(
+            serde_json::to_string(&action)?,
+            r#"{"type":"subAccountTransfer","subAccountUser":"0x5e9ee1089755c3435139848e47e6635505d5a13a","isDeposit":true,"usd":1000000}"#
+        );
+        // regression snapshot of the msgpack bytes, with the address packed as a lowercase hex string
+        assert_eq!(
+            hex::encode(rmp_serde::to_vec_named(&action)?),
+            "84a474797065b27375624163636f756e745472616e73666572ae7375624163636f756e7455736572d92a307835653965653130383937353563333433353133393834386534376536363335353035643561313361a969734465706f736974c3a3757364ce000f4240"
+        );
+        let connection_id = action.hash(1741146714088, Address::ZERO)?;
+        assert_eq!(
+            connection_id.to_debug_string(),
+            "0x3e908298d309a69b042f21a469526a918a9c54be8d9d3ae588108993cafa937d"
+        );
+
+        let action = Action::VaultTransfer {
+            vault_address: "0xdfc24b077bc1425ad1dea75bcb6f8158e10df303".parse()?,
+            is_deposit: false,
+            usd: usd_to_micros("25.5".parse()?)?,
+        };
+        assert_eq!(
+            hex::encode(rmp_serde::to_vec_named(&action)?),
+            "84a474797065ad7661756c745472616e73666572ac7661756c7441646472657373d92a307864666332346230373762633134323561643164656137356263623666383135386531306466333033a969734465706f736974c2a3757364ce01851960"
+        );
+        let connection_id = action.hash(1741146714088, Address::ZERO)?;
+        assert_eq!(
+            connection_id.to_debug_string(),
+            "0xab9b594687bd6e63bca131ba323f20aa13041366e5ea72c325e53bf5c8b6ac00"
+        );
+
+        assert!(usd_to_micros("0.0000001".parse()?).is_err());
+        assert!(usd_to_micros("-1".parse()?).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_vault_bound_client() -> eyre::Result<()> {
+        let sub_account: Address = "0x5e9ee1089755c3435139848e47e6635505d5a13a".parse()?;
+        let (host, server) = serve_once(
+            200,
+            r#"{"status":"ok","response":{"type":"order","data":{"statuses":[{"resting":{"oid":77}}]}}}"#,
+        )
+        .await;
+        let master = local_client(host);
+        let client = master.for_vault(Some(sub_account));
+        assert_eq!(client.address(), master.address());
+        client
+            .place_orders(vec![limit_order()], Grouping::Na)
+            .await?;
+        // nonces are shared with the master client
+        assert_eq!(master.nonce_manager().last(), client.nonce_manager().last());
+
+        let body: serde_json::Value = serde_json::from_str(&server.await?.body)?;
+        assert_eq!(
+            body["vaultAddress"],
+            "0x5e9ee1089755c3435139848e47e6635505d5a13a"
+        );
+        let connection_id = Action::Order {
+            orders: vec![limit_order()],
+            grouping: Grouping::Na,
+        }
+        .hash(body["nonce"].as_u64().unwrap(), sub_account)?;
+        let signing_hash = agent_sol::Agent {
+            source: "a".to_string(),
+            connectionId: connection_id,
+        }
+        .eip712_signing_hash(L1_DOMAIN);
+        assert_eq!(recover_signer(&body, signing_hash)?, master.address());
+
+        // account management is always signed for the master account itself
+        let (host, server) = serve_once(
+            200,
+            r#"{"status":"ok","response":{"type":"createSubAccount","data":"0x5e9ee1089755c3435139848e47e6635505d5a13a"}}"#,
+        )
+        .await;
+        let client = local_client(host).with_vault_address(Some(sub_account));
+        let created = client.create_sub_account("strategy-b").await?;
+        assert_eq!(created, sub_account);
+        let body: serde_json::Value = serde_json::from_str(&server.await?.body)?;
+        assert_eq!(
+            body["action"],
+            serde_json::json!({"type": "createSubAccount", "name": "strategy-b"})
+        );
+        assert!(body.get("vaultAddress").is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cancel_rejected_by_exchange() -> eyre::Result<()> {
+        let (host, server) = serve_once(
+            200,
+            r#"{"status":"err","response":"User or API Wallet does not exist."}"#,
+        )
+        .await;
+        let client = local_client(host);
+        let result = client
+            .cancel(vec![CancelRequest { asset: 0, oid: 123 }])
+            .await;
+        assert!(matches!(result, Err(Error::ExchangeError(_))), "{result:?}");
+
+        let body: serde_json::Value = serde_json::from_str(&server.await?.body)?;
+        assert_eq!(body["action"]["type"], "cancel");
+        Ok(())
+    }
 }
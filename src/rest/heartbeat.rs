@@ -0,0 +1,160 @@
+use crate::error::{Error, Result};
+use crate::rest::exchange::HyperliquidExchangeClient;
+use crate::utils::get_timestamp_ms;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{oneshot, watch};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// Dead man's switch built on `scheduleCancel`.
+///
+/// Every `interval` the heartbeat pushes the scheduled cancel to `timeout` from now. While the
+/// process is healthy the cancel never fires; if it crashes or hangs, all open orders are
+/// cancelled once `timeout` elapses. The exchange only accepts a limited number of triggered
+/// scheduled cancels per day, so pick a `timeout` comfortably longer than `interval`.
+pub struct CancelHeartbeat {
+    client: Arc<HyperliquidExchangeClient>,
+    scheduled: watch::Receiver<Option<u64>>,
+    stop: Option<oneshot::Sender<()>>,
+    task: JoinHandle<()>,
+}
+
+impl CancelHeartbeat {
+    /// Start the heartbeat. `interval` must be shorter than `timeout`
+    pub fn spawn(
+        client: Arc<HyperliquidExchangeClient>,
+        timeout: Duration,
+        interval: Duration,
+    ) -> Result<Self> {
+        if interval.is_zero() || interval >= timeout {
+            return Err(Error::InvalidConfig(format!(
+                "heartbeat interval {interval:?} must be shorter than the cancel timeout {timeout:?}"
+            )));
+        }
+        let (tx, scheduled) = watch::channel(None);
+        let (stop, stopped) = oneshot::channel();
+        let task = tokio::spawn(beat(client.clone(), timeout, interval, tx, stopped));
+        Ok(Self {
+            client,
+            scheduled,
+            stop: Some(stop),
+            task,
+        })
+    }
+
+    /// Time (ms) of the cancel most recently accepted by the exchange
+    pub fn scheduled_time(&self) -> Option<u64> {
+        *self.scheduled.borrow()
+    }
+
+    /// Wait until the exchange accepted a new cancel time
+    pub async fn wait_scheduled(&mut self) -> Option<u64> {
+        self.scheduled.changed().await.ok()?;
+        *self.scheduled.borrow_and_update()
+    }
+
+    /// Stop the heartbeat but leave the last scheduled cancel in place, so it will fire
+    pub fn stop(self) {
+        self.task.abort();
+    }
+
+    /// Stop the heartbeat and clear the scheduled cancel on the exchange. A beat in flight is
+    /// let through first, so it cannot schedule a cancel again after the clear
+    pub async fn disarm(mut self) -> Result<()> {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        let _ = (&mut self.task).await;
+        self.client.schedule_cancel(None).await
+    }
+}
+
+impl Drop for CancelHeartbeat {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn beat(
+    client: Arc<HyperliquidExchangeClient>,
+    timeout: Duration,
+    interval: Duration,
+    scheduled: watch::Sender<Option<u64>>,
+    mut stopped: oneshot::Receiver<()>,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = &mut stopped => return,
+        }
+        let result = async {
+            let time = get_timestamp_ms()? + timeout.as_millis() as u64;
+            client.schedule_cancel(Some(time)).await?;
+            Result::Ok(time)
+        }
+        .await;
+        match result {
+            Ok(time) => {
+                scheduled.send_replace(Some(time));
+            }
+            // the previous schedule still stands; retry on the next tick
+            Err(err) => warn!("Failed to push back scheduled cancel: {err}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::exchange::tests::local_client;
+    use crate::rest::helper::tests::serve_all;
+
+    #[tokio::test]
+    async fn test_heartbeat_pushes_cancel_forward() -> eyre::Result<()> {
+        let (host, mut requests) =
+            serve_all(200, r#"{"status":"ok","response":{"type":"default"}}"#).await;
+        let client = Arc::new(local_client(host));
+        let mut heartbeat =
+            CancelHeartbeat::spawn(client, Duration::from_secs(10), Duration::from_millis(50))?;
+
+        let mut times = vec![];
+        for _ in 0..3 {
+            let body: serde_json::Value =
+                serde_json::from_str(&requests.recv().await.unwrap().body)?;
+            assert_eq!(body["action"]["type"], "scheduleCancel");
+            times.push(body["action"]["time"].as_u64().unwrap());
+        }
+        assert!(times.windows(2).all(|w| w[0] <= w[1]));
+        assert!(times[0] >= get_timestamp_ms()? + 9000);
+        let scheduled = heartbeat.wait_scheduled().await.unwrap();
+        assert!(scheduled >= times[0]);
+
+        heartbeat.disarm().await?;
+        // skip any beat that was already in flight; nothing follows the clearing request
+        loop {
+            let body: serde_json::Value =
+                serde_json::from_str(&requests.recv().await.unwrap().body)?;
+            if body["action"].get("time").is_none() {
+                assert_eq!(
+                    body["action"],
+                    serde_json::json!({"type": "scheduleCancel"})
+                );
+                break;
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert!(requests.try_recv().is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_rejects_interval_not_below_timeout() {
+        let client = Arc::new(local_client("http://127.0.0.1:1".to_string()));
+        for interval in [Duration::ZERO, Duration::from_secs(10)] {
+            let result = CancelHeartbeat::spawn(client.clone(), Duration::from_secs(10), interval);
+            assert!(matches!(result, Err(Error::InvalidConfig(_))));
+        }
+    }
+}
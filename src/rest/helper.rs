@@ -1,27 +1,223 @@
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::rest::models::API;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use static_assertions::assert_impl_all;
+use tracing::debug;
 
 #[derive(Debug, Clone)]
 pub struct HyperliquidRestClientHelper {
+    client: reqwest::Client,
     host: String,
 }
 
 impl HyperliquidRestClientHelper {
     pub fn new(host: String) -> Self {
-        Self { host }
+        Self {
+            client: reqwest::Client::new(),
+            host,
+        }
     }
 }
 
 impl HyperliquidRestClientHelper {
-    pub fn build_request(&self, endpoint: API, req: impl Serialize) -> reqwest::Request {
-        todo!()
+    pub fn build_request(&self, endpoint: API, req: impl Serialize) -> Result<reqwest::Request> {
+        let url = format!("{}{}", self.host, endpoint.as_str());
+        let body = serde_json::to_vec(&req)?;
+        let request = self
+            .client
+            .post(url)
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .build()?;
+        Ok(request)
     }
     pub async fn post<T: DeserializeOwned>(&self, endpoint: API, req: impl Serialize) -> Result<T> {
-        todo!()
+        let request = self.build_request(endpoint, req)?;
+        debug!("POST {}", request.url());
+        let response = self.client.execute(request).await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(Error::response_error(status.as_u16(), body));
+        }
+        Ok(serde_json::from_str(&body)?)
     }
 }
 
 assert_impl_all!(HyperliquidRestClientHelper: Send, Sync, Unpin);
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::rest::models::InfoRequest;
+    use std::collections::HashMap;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::mpsc;
+    use tokio::task::JoinHandle;
+
+    /// A request captured by [`serve_once`]
+    pub struct CapturedRequest {
+        pub head: String,
+        pub body: String,
+    }
+
+    /// Serve a single HTTP response on a local port and hand back the request that was received.
+    pub async fn serve_once(
+        status: u16,
+        response_body: &'static str,
+    ) -> (String, JoinHandle<CapturedRequest>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let host = format!("http://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            respond(socket, status, response_body).await
+        });
+        (host, handle)
+    }
+
+    /// Like [`serve_once`], but keeps answering every request with the same response
+    pub async fn serve_all(
+        status: u16,
+        response_body: &'static str,
+    ) -> (String, mpsc::UnboundedReceiver<CapturedRequest>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let host = format!("http://{}", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            loop {
+                let (socket, _) = listener.accept().await.unwrap();
+                if tx
+                    .send(respond(socket, status, response_body).await)
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+        (host, rx)
+    }
+
+    /// Answer successive requests with `responses`, in order, with status 200
+    pub async fn serve_sequence(
+        responses: Vec<String>,
+    ) -> (String, mpsc::UnboundedReceiver<CapturedRequest>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let host = format!("http://{}", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            for response_body in responses {
+                let (socket, _) = listener.accept().await.unwrap();
+                let _ = tx.send(respond(socket, 200, &response_body).await);
+            }
+        });
+        (host, rx)
+    }
+
+    async fn respond(mut socket: TcpStream, status: u16, response_body: &str) -> CapturedRequest {
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 4096];
+        let (head, body) = loop {
+            let n = socket.read(&mut chunk).await.unwrap();
+            buf.extend_from_slice(&chunk[..n]);
+            let text = String::from_utf8_lossy(&buf).to_string();
+            if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                let content_length = head
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse::<usize>().unwrap())
+                    })
+                    .unwrap_or(0);
+                if body.len() >= content_length {
+                    break (head.to_string(), body.to_string());
+                }
+            }
+            if n == 0 {
+                panic!("connection closed before request was complete");
+            }
+        };
+        let response = format!(
+            "HTTP/1.1 {} Test\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            status,
+            response_body.len(),
+            response_body
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+        socket.shutdown().await.unwrap();
+        CapturedRequest { head, body }
+    }
+
+    #[tokio::test]
+    async fn test_build_request() -> eyre::Result<()> {
+        let helper = HyperliquidRestClientHelper::new("https://api.hyperliquid.xyz".to_string());
+        let request = helper.build_request(API::Info, &InfoRequest::AllMids)?;
+        assert_eq!(request.method(), http::Method::POST);
+        assert_eq!(request.url().as_str(), "https://api.hyperliquid.xyz/info");
+        assert_eq!(
+            request.headers()[http::header::CONTENT_TYPE],
+            "application/json"
+        );
+        let body = request.body().and_then(|b| b.as_bytes()).unwrap();
+        assert_eq!(body, br#"{"type":"allMids"}"#);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_post_success() -> eyre::Result<()> {
+        let (host, server) = serve_once(200, r#"{"BTC":"65000.5","ETH":"3500.1"}"#).await;
+        let helper = HyperliquidRestClientHelper::new(host);
+        let mids: HashMap<String, String> = helper.post(API::Info, &InfoRequest::AllMids).await?;
+        assert_eq!(mids["BTC"], "65000.5");
+        assert_eq!(mids["ETH"], "3500.1");
+
+        let captured = server.await?;
+        assert!(captured.head.starts_with("POST /info HTTP/1.1"));
+        assert_eq!(captured.body, r#"{"type":"allMids"}"#);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_post_client_error() -> eyre::Result<()> {
+        let (host, _server) = serve_once(422, "Failed to deserialize the JSON body").await;
+        let helper = HyperliquidRestClientHelper::new(host);
+        let result: Result<HashMap<String, String>> =
+            helper.post(API::Info, &InfoRequest::AllMids).await;
+        match result {
+            Err(Error::ResponseError { status, body }) => {
+                assert_eq!(status, 422);
+                assert_eq!(body, "Failed to deserialize the JSON body");
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_post_server_error() -> eyre::Result<()> {
+        let (host, _server) = serve_once(502, "bad gateway").await;
+        let helper = HyperliquidRestClientHelper::new(host);
+        let result: Result<HashMap<String, String>> =
+            helper.post(API::Exchange, &InfoRequest::AllMids).await;
+        match result {
+            Err(Error::ResponseError { status, body }) => {
+                assert_eq!(status, 502);
+                assert_eq!(body, "bad gateway");
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_post_malformed_json() -> eyre::Result<()> {
+        let (host, _server) = serve_once(200, r#"{"BTC":"65000.5""#).await;
+        let helper = HyperliquidRestClientHelper::new(host);
+        let result: Result<HashMap<String, String>> =
+            helper.post(API::Info, &InfoRequest::AllMids).await;
+        assert!(matches!(result, Err(Error::Json(_))), "{result:?}");
+        Ok(())
+    }
+}
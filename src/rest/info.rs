@@ -1,13 +1,42 @@
+use crate::error::{Error, Result};
 use crate::execution::models::HyperliquidChain;
 use crate::rest::helper::HyperliquidRestClientHelper;
 use crate::rest::models::{
-    API, AssetContext, CandleSnapshot, CandleSnapshotRequest, FundingHistory, L2Book, OpenOrder,
-    OrderStatusResponse, Request, SpotMetaTokenUniverse, Universe, UserFill, UserFunding,
-    UserState,
+    API, CandleInterval, CandleSnapshot, CandleSnapshotRequest, FundingHistory, L2Book,
+    MetaAndAssetContexts, OpenOrder, OrderStatusResponse, Request, SpotMetaAndAssetContexts,
+    SpotMetaTokenUniverse, SpotUserState, Universe, UserFill, UserFunding, UserState,
 };
-use crate::{HyperliquidUrls, error::Result};
+use crate::utils::get_timestamp_ms;
+use crate::{Decimal, HyperliquidUrls};
 use alloy::primitives::Address;
-use std::collections::HashMap;
+use futures::{Stream, StreamExt, TryStreamExt, stream};
+use std::collections::{HashMap, HashSet};
+
+/// Maximum number of fills returned by a single `userFillsByTime` call
+pub const FILLS_PAGE_LIMIT: usize = 2000;
+
+/// Order in which [`HyperliquidInfoClient::user_fills_history`] yields fills
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillOrder {
+    /// Stream fills as each page arrives
+    OldestFirst,
+    /// Download the whole window first, then yield it in reverse
+    NewestFirst,
+}
+
+/// Maximum number of candles returned by a single `candleSnapshot` call
+pub const CANDLES_PAGE_LIMIT: u64 = 5000;
+
+struct FillCursor {
+    start_time: u64,
+    seen: HashSet<u64>,
+}
+
+struct CandleCursor {
+    start_time: u64,
+    end_time: u64,
+    last: Option<CandleSnapshot>,
+}
 
 /// Endpoint to fetch information about the exchange and specific users.
 pub struct HyperliquidInfoClient {
@@ -35,13 +64,20 @@ impl HyperliquidInfoClient {
     pub async fn spot_metadata(&self) -> Result<SpotMetaTokenUniverse> {
         self.client.post(API::Info, &Request::SpotMeta).await
     }
+
+    /// Retrieve spot metadata with mark price, volume and supply of every pair
+    pub async fn spot_contexts(&self) -> Result<SpotMetaAndAssetContexts> {
+        self.client
+            .post(API::Info, &Request::SpotMetaAndAssetCtxs)
+            .await
+    }
     /// Retrieve all mids for all actively traded coins
     pub async fn mids(&self) -> Result<HashMap<String, String>> {
         self.client.post(API::Info, &Request::AllMids).await
     }
 
     /// Retrieve asset contexts i.e mark price, current funding, open interest, etc
-    pub async fn contexts(&self) -> Result<Vec<AssetContext>> {
+    pub async fn contexts(&self) -> Result<MetaAndAssetContexts> {
         self.client
             .post(API::Info, &Request::MetaAndAssetCtxs)
             .await
@@ -54,6 +90,13 @@ impl HyperliquidInfoClient {
             .await
     }
 
+    /// Retrieve a user's spot token balances
+    pub async fn spot_user_state(&self, user: Address) -> Result<SpotUserState> {
+        self.client
+            .post(API::Info, &Request::SpotClearinghouseState { user })
+            .await
+    }
+
     /// Retrieve a user's open orders
     pub async fn open_orders(&self, user: Address) -> Result<Vec<OpenOrder>> {
         self.client
@@ -74,6 +117,96 @@ impl HyperliquidInfoClient {
             .await
     }
 
+    /// Retrieve a user's fills between `start_time` and `end_time` (ms), at most
+    /// [`FILLS_PAGE_LIMIT`] per call
+    pub async fn user_fills_by_time(
+        &self,
+        user: Address,
+        start_time: u64,
+        end_time: Option<u64>,
+    ) -> Result<Vec<UserFill>> {
+        self.client
+            .post(
+                API::Info,
+                &Request::UserFillsByTime {
+                    user,
+                    startTime: start_time,
+                    endTime: end_time,
+                },
+            )
+            .await
+    }
+
+    /// Stream every fill between `start_time` and `end_time`, paging past the per-call limit.
+    /// Each page returns the oldest fills of its window, so the time of the last fill becomes
+    /// the start of the next one. Pages overlap at that timestamp, so fills are deduplicated by
+    /// `tid`
+    pub fn user_fills_history(
+        &self,
+        user: Address,
+        start_time: u64,
+        end_time: Option<u64>,
+        order: FillOrder,
+    ) -> impl Stream<Item = Result<UserFill>> + '_ {
+        match order {
+            FillOrder::OldestFirst => self
+                .user_fills_oldest_first(user, start_time, end_time)
+                .left_stream(),
+            FillOrder::NewestFirst => stream::once(async move {
+                let mut fills: Vec<UserFill> = self
+                    .user_fills_oldest_first(user, start_time, end_time)
+                    .try_collect()
+                    .await?;
+                fills.reverse();
+                Ok::<_, Error>(stream::iter(fills.into_iter().map(Ok)))
+            })
+            .try_flatten()
+            .right_stream(),
+        }
+    }
+
+    fn user_fills_oldest_first(
+        &self,
+        user: Address,
+        start_time: u64,
+        end_time: Option<u64>,
+    ) -> impl Stream<Item = Result<UserFill>> + '_ {
+        let cursor = FillCursor {
+            start_time,
+            seen: HashSet::new(),
+        };
+        stream::try_unfold(Some(cursor), move |cursor| async move {
+            let Some(mut cursor) = cursor else {
+                return Ok::<_, Error>(None);
+            };
+            let page = self
+                .user_fills_by_time(user, cursor.start_time, end_time)
+                .await?;
+            let exhausted = page.len() < FILLS_PAGE_LIMIT;
+            let last_time = page.iter().map(|fill| fill.time).max();
+            let mut fills: Vec<UserFill> = page
+                .into_iter()
+                .filter(|fill| cursor.seen.insert(fill.tid))
+                .collect();
+            fills.sort_by_key(|fill| (fill.time, fill.tid));
+
+            // a full page within a single millisecond cannot move the window; skip past it
+            let next_start = match last_time {
+                Some(time) if time > cursor.start_time => Some(time),
+                _ => cursor.start_time.checked_add(1),
+            };
+            let next = match next_start {
+                Some(start) if !exhausted && end_time.is_none_or(|end| start <= end) => {
+                    cursor.start_time = start;
+                    Some(cursor)
+                }
+                _ => None,
+            };
+            Ok(Some((stream::iter(fills.into_iter().map(Ok)), next)))
+        })
+        .try_flatten()
+    }
+
     /// Retrieve a user's funding history
     pub async fn user_funding(
         &self,
@@ -121,7 +254,7 @@ impl HyperliquidInfoClient {
     pub async fn candle_snapshot(
         &self,
         coin: String,
-        interval: String,
+        interval: CandleInterval,
         start_time: u64,
         end_time: u64,
     ) -> Result<Vec<CandleSnapshot>> {
@@ -140,6 +273,80 @@ impl HyperliquidInfoClient {
             .await
     }
 
+    /// Stream every candle between `start_time` and `end_time` in chronological order, splitting
+    /// the range into requests of at most [`CANDLES_PAGE_LIMIT`] candles.
+    ///
+    /// Candles repeated across requests are dropped. Intervals without a candle between two
+    /// returned ones are filled with a flat, zero-volume candle at the previous close, so the
+    /// series has no gaps; nothing is filled before the first or after the last candle.
+    /// `end_time` is capped at the current time, so `u64::MAX` means "until now".
+    pub fn candle_history(
+        &self,
+        coin: String,
+        interval: CandleInterval,
+        start_time: u64,
+        end_time: u64,
+    ) -> impl Stream<Item = Result<CandleSnapshot>> + '_ {
+        let cursor = CandleCursor {
+            start_time: interval.open_time(start_time),
+            end_time,
+            last: None,
+        };
+        stream::try_unfold(Some(cursor), move |cursor| {
+            let coin = coin.clone();
+            async move {
+                let Some(mut cursor) = cursor else {
+                    return Ok::<_, Error>(None);
+                };
+                // capped once, so the last window does not chase the clock
+                cursor.end_time = cursor.end_time.min(get_timestamp_ms()?);
+                if cursor.start_time > cursor.end_time {
+                    return Ok(None);
+                }
+                let window_end = cursor
+                    .start_time
+                    .saturating_add(CANDLES_PAGE_LIMIT * interval.duration_ms() - 1)
+                    .min(cursor.end_time);
+                let mut page = self
+                    .candle_snapshot(coin, interval, cursor.start_time, window_end)
+                    .await?;
+                page.sort_by_key(|candle| candle.time_start);
+
+                let mut candles = vec![];
+                for candle in page {
+                    if let Some(last) = &cursor.last {
+                        if candle.time_start <= last.time_start {
+                            continue;
+                        }
+                        let mut open_time = interval.next_open_time(last.time_start);
+                        while open_time < candle.time_start {
+                            let next_open_time = interval.next_open_time(open_time);
+                            candles.push(CandleSnapshot {
+                                time_start: open_time,
+                                time_end: next_open_time - 1,
+                                open: last.close,
+                                high: last.close,
+                                low: last.close,
+                                volume: Decimal::ZERO,
+                                trades: 0,
+                                ..last.clone()
+                            });
+                            open_time = next_open_time;
+                        }
+                    }
+                    cursor.last = Some(candle.clone());
+                    candles.push(candle);
+                }
+                let next = window_end.checked_add(1).map(|start_time| {
+                    cursor.start_time = start_time;
+                    cursor
+                });
+                Ok(Some((stream::iter(candles.into_iter().map(Ok)), next)))
+            }
+        })
+        .try_flatten()
+    }
+
     /// Query the status of an order by order ID
     pub async fn order_status(&self, user: Address, oid: u64) -> Result<OrderStatusResponse> {
         self.client
@@ -158,3 +365,409 @@ impl HyperliquidInfoClient {
             .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::helper::tests::{serve_once, serve_sequence};
+    use crate::rest::models::Leverage;
+
+    fn local_client(host: String) -> HyperliquidInfoClient {
+        let mut config = HyperliquidUrls::from_chain(HyperliquidChain::Arbitrum);
+        config.set_rest_endpoint(host);
+        HyperliquidInfoClient::new_with_config(HyperliquidChain::Arbitrum, &config)
+    }
+
+    #[tokio::test]
+    async fn test_contexts() -> eyre::Result<()> {
+        let (host, server) = serve_once(
+            200,
+            r#"[{"universe":[{"szDecimals":5,"name":"BTC","maxLeverage":40,"marginTableId":56},{"szDecimals":4,"name":"ETH","maxLeverage":25,"marginTableId":55},{"szDecimals":0,"name":"MATIC","maxLeverage":20,"marginTableId":20,"isDelisted":true}],"marginTables":[[56,{"description":"tiered 40x","marginTiers":[{"lowerBound":"0.0","maxLeverage":40}]}]]},[{"funding":"0.0000125","openInterest":"23458.28328","prevDayPx":"103620.0","dayNtlVlm":"1817553442.0906207561","premium":"-0.0001925927","oraclePx":"103843.0","markPx":"103830.0","midPx":"103823.5","impactPxs":["103823.0","103824.0"],"dayBaseVlm":"17562.26216"},{"funding":"-0.0000047891","openInterest":"584172.6814","prevDayPx":"2433.8","dayNtlVlm":"881293341.3463197947","premium":"-0.0003461538","oraclePx":"2444.0","markPx":"2443.2","midPx":"2443.15","impactPxs":["2443.1","2443.2"],"dayBaseVlm":"359876.7296"},{"funding":"0.0","openInterest":"0.0","prevDayPx":"0.37472","dayNtlVlm":"0.0","premium":null,"oraclePx":"0.37472","markPx":"0.37472","midPx":null,"impactPxs":null,"dayBaseVlm":"0.0"}]]"#,
+        )
+        .await;
+        let contexts = local_client(host).contexts().await?;
+        assert_eq!(server.await?.body, r#"{"type":"metaAndAssetCtxs"}"#);
+
+        assert_eq!(contexts.iter().count(), 3);
+        let (eth, ctx) = contexts.get("ETH").unwrap();
+        assert_eq!((eth.szDecimals, eth.maxLeverage), (4, 25));
+        assert_eq!(ctx.funding, "-0.0000047891".parse()?);
+        assert_eq!(ctx.openInterest, "584172.6814".parse()?);
+        assert_eq!(ctx.markPx, "2443.2".parse()?);
+        assert_eq!(ctx.oraclePx, "2444".parse()?);
+        assert_eq!(ctx.premium, Some("-0.0003461538".parse()?));
+        assert_eq!(ctx.impactPxs, Some(["2443.1".parse()?, "2443.2".parse()?]));
+
+        let (matic, ctx) = contexts.get("MATIC").unwrap();
+        assert!(matic.isDelisted);
+        assert_eq!((ctx.premium, ctx.midPx, ctx.impactPxs), (None, None, None));
+        assert!(contexts.get("DOGE").is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_user_state() -> eyre::Result<()> {
+        let (host, server) = serve_once(
+            200,
+            r#"{"marginSummary":{"accountValue":"13109.482328","totalNtlPos":"3102.84","totalRawUsd":"16212.32","totalMarginUsed":"253.12"},"crossMarginSummary":{"accountValue":"12909.482328","totalNtlPos":"2002.84","totalRawUsd":"14912.32","totalMarginUsed":"100.14"},"crossMaintenanceMarginUsed":"50.07","withdrawable":"12656.362328","assetPositions":[{"type":"oneWay","position":{"coin":"ETH","szi":"0.8195","leverage":{"type":"cross","value":20},"entryPx":"2441.2","positionValue":"2002.84","unrealizedPnl":"2.27","returnOnEquity":"0.0226907","liquidationPx":null,"marginUsed":"100.142","maxLeverage":25,"cumFunding":{"allTime":"514.085417","sinceOpen":"0.0","sinceChange":"0.0"}}},{"type":"oneWay","position":{"coin":"BTC","szi":"-0.0106","leverage":{"type":"isolated","value":10,"rawUsd":"1253.4"},"entryPx":"103750.0","positionValue":"1100.0","unrealizedPnl":"-0.3","returnOnEquity":"-0.0027272","liquidationPx":"113266.4412","marginUsed":"152.98","maxLeverage":40,"cumFunding":{"allTime":"-1.25","sinceOpen":"-0.12","sinceChange":"-0.12"}}}],"time":1708622398623}"#,
+        )
+        .await;
+        let user: Address = "0x5e9ee1089755c3435139848e47e6635505d5a13a".parse()?;
+        let state = local_client(host).user_state(user).await?;
+        assert_eq!(
+            server.await?.body,
+            r#"{"type":"clearinghouseState","user":"0x5e9ee1089755c3435139848e47e6635505d5a13a"}"#
+        );
+        assert_eq!(state.margin_summary.account_value, "13109.482328".parse()?);
+        assert_eq!(
+            state.cross_margin_summary.total_margin_used,
+            "100.14".parse()?
+        );
+        assert_eq!(state.cross_maintenance_margin_used, "50.07".parse()?);
+        assert_eq!(state.withdrawable, "12656.362328".parse()?);
+        assert_eq!(state.time, 1708622398623);
+
+        let eth = state.position("ETH").unwrap();
+        assert!(eth.is_long());
+        assert_eq!(eth.leverage, Leverage::Cross { value: 20 });
+        assert_eq!(eth.liquidation_px, None);
+        assert_eq!(eth.max_leverage, 25);
+        assert_eq!(eth.cum_funding.all_time, "514.085417".parse()?);
+
+        let btc = state.position("BTC").unwrap();
+        assert!(!btc.is_long());
+        assert!(!btc.leverage.is_cross());
+        assert_eq!(btc.leverage.value(), 10);
+        assert_eq!(btc.liquidation_px, Some("113266.4412".parse()?));
+        assert_eq!(btc.margin_used, "152.98".parse()?);
+        assert_eq!(btc.return_on_equity, "-0.0027272".parse()?);
+        assert_eq!(state.asset_positions[1].position_type, "oneWay");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_open_orders_and_order_status() -> eyre::Result<()> {
+        let user: Address = "0x5e9ee1089755c3435139848e47e6635505d5a13a".parse()?;
+        let (host, mut requests) = serve_sequence(vec![
+            r#"[{"coin":"BTC","side":"A","limitPx":"29792.0","sz":"0.0","oid":91490942,"timestamp":1681247412573,"origSz":"5.0","cloid":"0x9c09a42dede9495ea86bb4bc3888cc2d"}]"#.to_string(),
+            r#"{"status":"order","order":{"order":{"coin":"ETH","side":"A","limitPx":"2412.7","sz":"0.0","oid":1,"timestamp":1724361546645,"triggerCondition":"N/A","isTrigger":false,"triggerPx":"0.0","children":[],"isPositionTpsl":false,"reduceOnly":true,"orderType":"Market","origSz":"0.0076","tif":"FrontendMarket","cloid":null},"status":"filled","statusTimestamp":1724361546645}}"#.to_string(),
+            r#"{"status":"unknownOid"}"#.to_string(),
+        ])
+        .await;
+        let client = local_client(host);
+
+        let orders = client.open_orders(user).await?;
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].limit_px, "29792".parse()?);
+        assert_eq!(orders[0].orig_sz, Some("5".parse()?));
+        assert_eq!(
+            orders[0].cloid.as_deref(),
+            Some("0x9c09a42dede9495ea86bb4bc3888cc2d")
+        );
+        assert!(!orders[0].reduce_only);
+        assert_eq!(orders[0].order_type, None);
+
+        let status = client.order_status(user, 1).await?;
+        let info = status.order.unwrap();
+        assert_eq!(
+            (status.status.as_str(), info.status.as_str()),
+            ("order", "filled")
+        );
+        assert_eq!(info.status_timestamp, 1724361546645);
+        assert_eq!(info.order.limit_px, "2412.7".parse()?);
+        assert!(info.order.reduce_only);
+        assert!(!info.order.is_trigger);
+        assert_eq!(info.order.order_type.as_deref(), Some("Market"));
+        assert_eq!(info.order.tif.as_deref(), Some("FrontendMarket"));
+
+        let status = client.order_status(user, 2).await?;
+        assert_eq!(status.status, "unknownOid");
+        assert!(status.order.is_none());
+
+        let body = requests.recv().await.unwrap().body;
+        assert_eq!(
+            body,
+            r#"{"type":"openOrders","user":"0x5e9ee1089755c3435139848e47e6635505d5a13a"}"#
+        );
+        Ok(())
+    }
+
+    fn fills_page(times: impl IntoIterator<Item = (u64, u64)>) -> String {
+        let fills: Vec<_> = times
+            .into_iter()
+            .map(|(time, tid)| {
+                serde_json::json!({
+                    "coin": "ETH", "px": "2443.1", "sz": "0.01", "side": "B", "time": time,
+                    "startPosition": "0.0", "dir": "Open Long", "closedPnl": "0.0",
+                    "hash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "oid": 1, "crossed": true, "fee": "0.010994", "tid": tid, "feeToken": "USDC"
+                })
+            })
+            .collect();
+        serde_json::to_string(&fills).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_user_fills_history_pages_forward() -> eyre::Result<()> {
+        let limit = FILLS_PAGE_LIMIT as u64;
+        // a full page ending at t=1999, then the rest, overlapping at the boundary timestamp
+        let responses = vec![
+            fills_page((0..limit).map(|i| (i, i))),
+            fills_page([(1999, 1999), (1999, 5000), (2500, 5001)]),
+        ];
+        let (host, mut requests) = serve_sequence(responses).await;
+        let client = local_client(host);
+        let user: Address = "0x5e9ee1089755c3435139848e47e6635505d5a13a".parse()?;
+        let fills: Vec<UserFill> = client
+            .user_fills_history(user, 0, Some(3000), FillOrder::OldestFirst)
+            .try_collect()
+            .await?;
+        assert_eq!(fills.len(), FILLS_PAGE_LIMIT + 2);
+        assert!(fills.windows(2).all(|w| w[0].time <= w[1].time));
+        assert_eq!(fills.last().unwrap().tid, 5001);
+
+        let first: serde_json::Value = serde_json::from_str(&requests.recv().await.unwrap().body)?;
+        assert_eq!(
+            first,
+            serde_json::json!({"type": "userFillsByTime", "user": "0x5e9ee1089755c3435139848e47e6635505d5a13a", "startTime": 0, "endTime": 3000})
+        );
+        let second: serde_json::Value = serde_json::from_str(&requests.recv().await.unwrap().body)?;
+        assert_eq!(second["startTime"], 1999);
+        assert_eq!(second["endTime"], 3000);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_user_fills_history_newest_first() -> eyre::Result<()> {
+        let limit = FILLS_PAGE_LIMIT as u64;
+        let responses = vec![
+            fills_page((0..limit).map(|i| (i, i))),
+            fills_page([(1999, 1999), (2500, 5001)]),
+        ];
+        let (host, mut requests) = serve_sequence(responses).await;
+        let client = local_client(host);
+        let user: Address = "0x5e9ee1089755c3435139848e47e6635505d5a13a".parse()?;
+        let fills: Vec<UserFill> = client
+            .user_fills_history(user, 0, None, FillOrder::NewestFirst)
+            .try_collect()
+            .await?;
+        assert_eq!(fills.len(), FILLS_PAGE_LIMIT + 1);
+        assert_eq!(fills[0].tid, 5001);
+        assert!(fills.windows(2).all(|w| w[0].time >= w[1].time));
+
+        // pages still walk forward from the start of the window
+        let first: serde_json::Value = serde_json::from_str(&requests.recv().await.unwrap().body)?;
+        assert_eq!(
+            (first["startTime"].as_u64(), first.get("endTime")),
+            (Some(0), None)
+        );
+        let second: serde_json::Value = serde_json::from_str(&requests.recv().await.unwrap().body)?;
+        assert_eq!(second["startTime"], 1999);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_user_fills_history_skips_repeated_page() -> eyre::Result<()> {
+        let limit = FILLS_PAGE_LIMIT as u64;
+        // more fills in a single millisecond than fit in a page
+        let responses = vec![
+            fills_page((0..limit).map(|i| (5, i))),
+            fills_page((0..limit).map(|i| (5, i))),
+            fills_page([(7, 9000)]),
+        ];
+        let (host, mut requests) = serve_sequence(responses).await;
+        let client = local_client(host);
+        let user: Address = "0x5e9ee1089755c3435139848e47e6635505d5a13a".parse()?;
+        let fills: Vec<UserFill> = client
+            .user_fills_history(user, 0, Some(100), FillOrder::OldestFirst)
+            .try_collect()
+            .await?;
+        assert_eq!(fills.len(), FILLS_PAGE_LIMIT + 1);
+        assert_eq!(fills.last().unwrap().tid, 9000);
+
+        let mut starts = vec![];
+        while let Ok(request) = requests.try_recv() {
+            let body: serde_json::Value = serde_json::from_str(&request.body)?;
+            starts.push(body["startTime"].as_u64().unwrap());
+        }
+        assert_eq!(starts, [0, 5, 6]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_spot_contexts() -> eyre::Result<()> {
+        let (host, server) = serve_once(
+            200,
+            r#"[{"universe":[{"tokens":[1,0],"name":"PURR/USDC","index":0,"isCanonical":true},{"tokens":[150,0],"name":"@107","index":107,"isCanonical":false}],"tokens":[{"name":"USDC","szDecimals":8,"weiDecimals":8,"index":0,"tokenId":"0x6d1e7cde53ba9467b783cb7c530ce054","isCanonical":true,"evmContract":null,"fullName":null,"deployerTradingFeeShare":"0.0"},{"name":"PURR","szDecimals":0,"weiDecimals":5,"index":1,"tokenId":"0xc1fb593aeffbeb02f85e0308e9956a90","isCanonical":true,"evmContract":null,"fullName":null,"deployerTradingFeeShare":"0.0"}]},[{"prevDayPx":"0.20432","dayNtlVlm":"11223.456","markPx":"0.20304","midPx":"0.20314","circulatingSupply":"598763992.88","coin":"PURR/USDC","totalSupply":"599999950.57","dayBaseVlm":"55012.0"},{"prevDayPx":"21.1","dayNtlVlm":"25001.1","markPx":"21.5","midPx":null,"circulatingSupply":"333000000.0","coin":"@107","totalSupply":"999999990.0","dayBaseVlm":"1170.0"}]]"#,
+        )
+        .await;
+        let spot = local_client(host).spot_contexts().await?;
+        assert_eq!(server.await?.body, r#"{"type":"spotMetaAndAssetCtxs"}"#);
+
+        assert_eq!(spot.meta.universe.len(), spot.contexts.len());
+        let pair = &spot.meta.universe[1];
+        assert_eq!(pair.asset(), 10107);
+        assert_eq!(spot.contexts[1].coin, pair.name);
+        assert_eq!(spot.contexts[0].markPx, "0.20304".parse()?);
+        assert_eq!(spot.contexts[1].midPx, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_spot_user_state() -> eyre::Result<()> {
+        let (host, server) = serve_once(
+            200,
+            r#"{"balances":[{"coin":"USDC","token":0,"hold":"0.0","total":"14.625485","entryNtl":"0.0"},{"coin":"PURR","token":1,"hold":"100.0","total":"2000","entryNtl":"1234.56"}]}"#,
+        )
+        .await;
+        let user: Address = "0x5e9ee1089755c3435139848e47e6635505d5a13a".parse()?;
+        let state = local_client(host).spot_user_state(user).await?;
+        assert_eq!(
+            server.await?.body,
+            r#"{"type":"spotClearinghouseState","user":"0x5e9ee1089755c3435139848e47e6635505d5a13a"}"#
+        );
+        assert_eq!(state.balances.len(), 2);
+        let purr = &state.balances[1];
+        assert_eq!((purr.coin.as_str(), purr.token), ("PURR", 1));
+        assert_eq!(purr.total - purr.hold, "1900".parse()?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_candle_snapshot() -> eyre::Result<()> {
+        let (host, server) = serve_once(
+            200,
+            r#"[{"t":1681923600000,"T":1681924499999,"s":"BTC","i":"15m","o":"29295.0","c":"29258.0","h":"29309.0","l":"29250.0","v":"0.98639","n":189}]"#,
+        )
+        .await;
+        let candles = local_client(host)
+            .candle_snapshot(
+                "BTC".to_string(),
+                CandleInterval::FifteenMinutes,
+                1681923600000,
+                1681924499999,
+            )
+            .await?;
+        assert_eq!(
+            server.await?.body,
+            r#"{"type":"candleSnapshot","req":{"coin":"BTC","interval":"15m","startTime":1681923600000,"endTime":1681924499999}}"#
+        );
+        let candle = &candles[0];
+        assert_eq!(
+            (candle.time_start, candle.time_end),
+            (1681923600000, 1681924499999)
+        );
+        assert_eq!(candle.interval, CandleInterval::FifteenMinutes);
+        assert_eq!(candle.symbol, "BTC");
+        assert_eq!(candle.open, "29295".parse()?);
+        assert_eq!(candle.high, "29309".parse()?);
+        assert_eq!(candle.low, "29250".parse()?);
+        assert_eq!(candle.close, "29258".parse()?);
+        assert_eq!(candle.volume, "0.98639".parse()?);
+        assert_eq!(candle.trades, 189);
+        Ok(())
+    }
+
+    fn candles_page(candles: impl IntoIterator<Item = (u64, &'static str)>) -> String {
+        let candles: Vec<_> = candles
+            .into_iter()
+            .map(|(minute, close)| {
+                let t = minute * 60_000;
+                serde_json::json!({"t": t, "T": t + 59_999, "s": "ETH", "i": "1m", "o": "2000.0", "c": close, "h": "2001.0", "l": "1999.0", "v": "1.5", "n": 3})
+            })
+            .collect();
+        serde_json::to_string(&candles).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_candle_history_chunks_and_fills_gaps() -> eyre::Result<()> {
+        let limit = CANDLES_PAGE_LIMIT;
+        let responses = vec![
+            // minute 2 had no trades
+            candles_page([
+                (0, "2000.5"),
+                (1, "2000.6"),
+                (3, "2000.7"),
+                (limit - 1, "2000.8"),
+            ]),
+            // the last candle of the previous chunk is returned again
+            candles_page([
+                (limit - 1, "2000.8"),
+                (limit, "2000.9"),
+                (limit + 5, "2001.0"),
+            ]),
+        ];
+        let (host, mut requests) = serve_sequence(responses).await;
+        let client = local_client(host);
+        let end_time = (limit + 10) * 60_000 - 1;
+        let candles: Vec<CandleSnapshot> = client
+            .candle_history(
+                "ETH".to_string(),
+                CandleInterval::OneMinute,
+                30_000,
+                end_time,
+            )
+            .try_collect()
+            .await?;
+
+        assert_eq!(candles.len() as u64, limit + 6);
+        assert!(
+            candles
+                .iter()
+                .enumerate()
+                .all(|(i, c)| c.time_start == i as u64 * 60_000
+                    && c.time_end == c.time_start + 59_999)
+        );
+        let filled = &candles[2];
+        assert_eq!(filled.close, "2000.6".parse()?);
+        assert_eq!(
+            (filled.open, filled.high, filled.low),
+            (filled.close, filled.close, filled.close)
+        );
+        assert_eq!((filled.volume, filled.trades), (Decimal::ZERO, 0));
+        assert_eq!(candles[3].trades, 3);
+        assert_eq!(candles[limit as usize + 4].close, "2000.9".parse()?);
+        assert_eq!(candles.last().unwrap().close, "2001".parse()?);
+
+        let first: serde_json::Value = serde_json::from_str(&requests.recv().await.unwrap().body)?;
+        assert_eq!(
+            first,
+            serde_json::json!({"type": "candleSnapshot", "req": {"coin": "ETH", "interval": "1m", "startTime": 0, "endTime": limit * 60_000 - 1}})
+        );
+        let second: serde_json::Value = serde_json::from_str(&requests.recv().await.unwrap().body)?;
+        assert_eq!(second["req"]["startTime"], limit * 60_000);
+        assert_eq!(second["req"]["endTime"], end_time);
+        assert!(requests.try_recv().is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_candle_history_until_now() -> eyre::Result<()> {
+        let now = get_timestamp_ms()?;
+        let start_time = CandleInterval::OneMinute.open_time(now) - 2 * 60_000;
+        let minute = start_time / 60_000;
+        let (host, mut requests) = serve_sequence(vec![candles_page([
+            (minute, "2000.5"),
+            (minute + 2, "2000.6"),
+        ])])
+        .await;
+        let candles: Vec<CandleSnapshot> = local_client(host)
+            .candle_history(
+                "ETH".to_string(),
+                CandleInterval::OneMinute,
+                start_time,
+                u64::MAX,
+            )
+            .try_collect()
+            .await?;
+        assert_eq!(candles.len(), 3);
+
+        let request: serde_json::Value =
+            serde_json::from_str(&requests.recv().await.unwrap().body)?;
+        assert_eq!(request["req"]["startTime"], start_time);
+        let end_time = request["req"]["endTime"].as_u64().unwrap();
+        assert!((now..=get_timestamp_ms()?).contains(&end_time));
+        assert!(requests.try_recv().is_err());
+        Ok(())
+    }
+}
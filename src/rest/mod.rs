@@ -1,7 +1,9 @@
 pub mod client;
+pub mod heartbeat;
 pub(crate) mod helper;
 pub mod info;
 pub mod models;
+pub mod registry;
 pub mod session;
 
 pub mod exchange;
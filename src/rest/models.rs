@@ -1,8 +1,9 @@
 // REST-related model definitions
+use crate::Decimal;
+pub use crate::market::models::{CandleInterval, CandleSnapshot, PriceLevel};
 use alloy::primitives::Address;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use serde_with::serde_as;
 
 // Enum for API endpoints
 pub enum API {
@@ -30,6 +31,10 @@ pub enum InfoRequest {
     SpotMeta,
     #[serde(rename = "metaAndAssetCtxs")]
     MetaAndAssetCtxs,
+    #[serde(rename = "spotMetaAndAssetCtxs")]
+    SpotMetaAndAssetCtxs,
+    #[serde(rename = "spotClearinghouseState")]
+    SpotClearinghouseState { user: Address },
     #[serde(rename = "clearinghouseState")]
     ClearinghouseState { user: Address },
     #[serde(rename = "userFills")]
@@ -37,19 +42,19 @@ pub enum InfoRequest {
         user: Address,
         startTime: Option<u64>,
     },
+    #[serde(rename = "userFillsByTime")]
+    UserFillsByTime {
+        user: Address,
+        startTime: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        endTime: Option<u64>,
+    },
     #[serde(rename = "userFunding")]
     UserFunding {
         user: Address,
         start_time: u64,
         end_time: Option<u64>,
     },
-    #[serde(rename = "candles")]
-    Candles {
-        coin: String,
-        interval: String,
-        startTime: Option<u64>,
-        endTime: Option<u64>,
-    },
     #[serde(rename = "openOrders")]
     OpenOrders { user: Address },
     #[serde(rename = "assetCtx")]
@@ -84,9 +89,10 @@ pub enum InfoRequest {
 pub type Request = InfoRequest;
 
 #[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct CandleSnapshotRequest {
     pub coin: String,
-    pub interval: String,
+    pub interval: CandleInterval,
     pub start_time: u64,
     pub end_time: u64,
 }
@@ -103,14 +109,83 @@ pub struct Universe {
 pub struct Asset {
     pub name: String,
     pub szDecimals: u32,
+    #[serde(default)]
+    pub maxLeverage: u32,
+    #[serde(default)]
+    pub onlyIsolated: bool,
+    #[serde(default)]
+    pub isDelisted: bool,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct SpotMetaTokenUniverse {
+    #[serde(default)]
+    pub universe: Vec<SpotPair>,
     pub tokens: Vec<SpotToken>,
 }
+/// A spot trading pair. `tokens` holds the base and quote token indices
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize)]
+pub struct SpotPair {
+    pub name: String,
+    pub tokens: [u32; 2],
+    pub index: u32,
+    pub isCanonical: bool,
+}
+
+impl SpotPair {
+    /// Asset id of the pair in exchange actions
+    pub fn asset(&self) -> u32 {
+        crate::rest::registry::SPOT_ASSET_OFFSET + self.index
+    }
+}
+
+/// Response of `spotMetaAndAssetCtxs`, sent as a `[meta, contexts]` pair
+#[derive(Debug, Deserialize)]
+#[serde(from = "(SpotMetaTokenUniverse, Vec<SpotAssetContext>)")]
+pub struct SpotMetaAndAssetContexts {
+    pub meta: SpotMetaTokenUniverse,
+    /// One context per pair, in the order of `meta.universe`
+    pub contexts: Vec<SpotAssetContext>,
+}
+
+impl From<(SpotMetaTokenUniverse, Vec<SpotAssetContext>)> for SpotMetaAndAssetContexts {
+    fn from((meta, contexts): (SpotMetaTokenUniverse, Vec<SpotAssetContext>)) -> Self {
+        Self { meta, contexts }
+    }
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize)]
+pub struct SpotAssetContext {
+    /// Pair name, matching [`SpotPair::name`]
+    pub coin: String,
+    pub dayNtlVlm: Decimal,
+    pub dayBaseVlm: Decimal,
+    pub markPx: Decimal,
+    pub midPx: Option<Decimal>,
+    pub prevDayPx: Decimal,
+    pub circulatingSupply: Decimal,
+    pub totalSupply: Decimal,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SpotUserState {
+    pub balances: Vec<SpotBalance>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize)]
+pub struct SpotBalance {
+    pub coin: String,
+    /// Token index
+    pub token: u32,
+    /// Amount reserved by open orders
+    pub hold: Decimal,
+    pub total: Decimal,
+    pub entryNtl: Decimal,
+}
 #[allow(non_snake_case)]
-#[serde_as]
 #[derive(Debug, Deserialize)]
 pub struct SpotToken {
     pub name: String,
@@ -121,135 +196,223 @@ pub struct SpotToken {
     pub isCanonical: bool,
     pub evmContract: Option<Value>,
     pub fullName: Option<String>,
-    #[serde_as(as = "serde_with::DisplayFromStr")]
-    pub deployerTradingFeeShare: f64,
+    pub deployerTradingFeeShare: Decimal,
+}
+/// Response of `metaAndAssetCtxs`, sent as a `[meta, contexts]` pair
+#[derive(Debug, Deserialize)]
+#[serde(from = "(Universe, Vec<AssetContext>)")]
+pub struct MetaAndAssetContexts {
+    pub meta: Universe,
+    /// One context per asset, in the order of `meta.universe`
+    pub contexts: Vec<AssetContext>,
+}
+
+impl From<(Universe, Vec<AssetContext>)> for MetaAndAssetContexts {
+    fn from((meta, contexts): (Universe, Vec<AssetContext>)) -> Self {
+        Self { meta, contexts }
+    }
+}
+
+impl MetaAndAssetContexts {
+    /// Each asset with its context
+    pub fn iter(&self) -> impl Iterator<Item = (&Asset, &AssetContext)> {
+        self.meta.universe.iter().zip(&self.contexts)
+    }
+    pub fn get(&self, coin: &str) -> Option<(&Asset, &AssetContext)> {
+        self.iter().find(|(asset, _)| asset.name == coin)
+    }
 }
+
 #[allow(non_snake_case)]
 #[derive(Debug, Deserialize)]
 pub struct AssetContext {
-    pub asset: Asset,
-    pub frozen: bool,
-    pub openInterest: f64,
-    pub midPrice: f64,
-    pub markPrice: f64,
-    pub fundingRate: f64,
-    pub oraclePx: f64,
+    /// Current hourly funding rate
+    pub funding: Decimal,
+    pub openInterest: Decimal,
+    pub prevDayPx: Decimal,
+    pub dayNtlVlm: Decimal,
+    pub dayBaseVlm: Option<Decimal>,
+    pub premium: Option<Decimal>,
+    pub oraclePx: Decimal,
+    pub markPx: Decimal,
+    pub midPx: Option<Decimal>,
+    /// Impact bid and ask prices
+    pub impactPxs: Option<[Decimal; 2]>,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct UserState {
-    pub withdrawable: f64,
+    /// Totals over cross and isolated positions
     pub margin_summary: MarginSummary,
-    pub time: u64,
+    pub cross_margin_summary: MarginSummary,
+    pub cross_maintenance_margin_used: Decimal,
+    pub withdrawable: Decimal,
     pub asset_positions: Vec<AssetPosition>,
+    pub time: u64,
+}
+
+impl UserState {
+    pub fn position(&self, coin: &str) -> Option<&Position> {
+        self.asset_positions
+            .iter()
+            .map(|p| &p.position)
+            .find(|p| p.coin == coin)
+    }
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct MarginSummary {
-    pub account_value: f64,
-    pub total_margin_used: f64,
-    pub total_ntl: f64,
-    pub total_margin_ratio: f64,
+    pub account_value: Decimal,
+    pub total_ntl_pos: Decimal,
+    pub total_raw_usd: Decimal,
+    pub total_margin_used: Decimal,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct AssetPosition {
+    /// e.g. `oneWay`
+    #[serde(rename = "type")]
+    pub position_type: String,
     pub position: Position,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Position {
     pub coin: String,
-    pub szi: f64,
-    pub entry_px: Option<f64>,
-    pub unrealized_pnl: f64,
+    /// Signed size, negative for shorts
+    pub szi: Decimal,
+    pub leverage: Leverage,
+    pub entry_px: Option<Decimal>,
+    pub position_value: Decimal,
+    pub unrealized_pnl: Decimal,
+    pub return_on_equity: Decimal,
+    pub liquidation_px: Option<Decimal>,
+    pub margin_used: Decimal,
+    pub max_leverage: u32,
+    pub cum_funding: CumFunding,
+}
+
+impl Position {
+    pub fn is_long(&self) -> bool {
+        self.szi.is_sign_positive() && !self.szi.is_zero()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Leverage {
+    Cross {
+        value: u32,
+    },
+    Isolated {
+        value: u32,
+        #[serde(rename = "rawUsd")]
+        raw_usd: Decimal,
+    },
+}
+
+impl Leverage {
+    pub fn value(&self) -> u32 {
+        match self {
+            Leverage::Cross { value } | Leverage::Isolated { value, .. } => *value,
+        }
+    }
+    pub fn is_cross(&self) -> bool {
+        matches!(self, Leverage::Cross { .. })
+    }
+}
+
+/// Funding paid by the position; positive means paid, negative received
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CumFunding {
+    pub all_time: Decimal,
+    pub since_open: Decimal,
+    pub since_change: Decimal,
 }
 
+/// Order as returned by `openOrders`; `orderStatus` adds the trigger and time in force details
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct OpenOrder {
     pub coin: String,
     pub side: String,
-    pub sz: String,
-    pub limit_px: String,
-    pub reduce_only: bool,
-    pub order_type: OrderType,
+    /// Remaining size
+    pub sz: Decimal,
+    pub limit_px: Decimal,
     pub oid: u64,
     pub timestamp: u64,
+    pub orig_sz: Option<Decimal>,
     pub cloid: Option<String>,
-}
-
-#[derive(Debug, Deserialize)]
-#[serde(tag = "type")]
-pub enum OrderType {
-    #[serde(rename = "limit")]
-    Limit { tif: String },
-    #[serde(rename = "trigger")]
-    Trigger {
-        is_market: bool,
-        trigger_px: String,
-        tpsl: String,
-    },
+    #[serde(default)]
+    pub reduce_only: bool,
+    /// e.g. `Limit`, `Market`, `Stop Market` or `Take Profit Limit`
+    pub order_type: Option<String>,
+    #[serde(default)]
+    pub is_trigger: bool,
+    pub trigger_px: Option<Decimal>,
+    pub tif: Option<String>,
 }
 
 #[allow(non_snake_case)]
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct UserFill {
     pub coin: String,
-    pub px: f64,
-    pub sz: f64,
+    pub px: Decimal,
+    pub sz: Decimal,
+    /// `B` for buys, `A` for sells
     pub side: String,
     pub time: u64,
-    pub startPosition: String,
+    pub startPosition: Decimal,
     pub dir: String,
-    pub closed_pnl: String,
+    pub closedPnl: Decimal,
     pub hash: String,
     pub oid: u64,
     pub crossed: bool,
-    pub fee: String,
+    pub fee: Decimal,
+    /// Unique trade id
     pub tid: u64,
-    pub fee_token: String,
+    pub feeToken: String,
+    pub cloid: Option<String>,
+    pub builderFee: Option<Decimal>,
+}
+
+impl UserFill {
+    /// Split `dir`, e.g. `Open Long`, into its action and side
+    pub fn dir(&self) -> (String, String) {
+        let parts: Vec<&str> = self.dir.split(' ').collect();
+        if parts.len() == 2 {
+            (parts[0].to_string(), parts[1].to_string())
+        } else {
+            (self.dir.clone(), "".to_string())
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct UserFunding {
     pub coin: String,
-    pub usdc: f64,
+    pub usdc: Decimal,
     pub time: u64,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct FundingHistory {
     pub coin: String,
-    pub funding: f64,
+    pub funding: Decimal,
     pub time: u64,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct L2Book {
     pub coin: String,
+    pub time: u64,
     pub levels: (Vec<PriceLevel>, Vec<PriceLevel>),
 }
 
-#[derive(Debug, Deserialize)]
-pub struct PriceLevel {
-    pub px: f64,
-    pub sz: f64,
-    pub n: Option<u32>,
-}
-
-#[derive(Debug, Deserialize)]
-pub struct CandleSnapshot {
-    pub symbol: String,
-    pub interval: String,
-    pub time_start: u64,
-    pub time_end: u64,
-    pub open: f64,
-    pub high: f64,
-    pub low: f64,
-    pub close: f64,
-    pub volume: f64,
-}
-
 #[derive(Debug, Deserialize)]
 pub struct UserPoints {
     pub user: String,
@@ -261,19 +424,21 @@ pub struct UserPoints {
 #[derive(Debug, Deserialize)]
 pub struct UserPoint {
     pub name: String,
-    pub score: f64,
+    pub score: Decimal,
 }
 
+/// `status` is `order`, or `unknownOid` with no `order`
 #[derive(Debug, Deserialize)]
 pub struct OrderStatusResponse {
     pub status: String,
-    pub coin: String,
-    pub side: String,
-    pub sz: String,
-    pub limit_px: String,
-    pub reduce_only: bool,
-    pub order_type: OrderType,
-    pub timestamp: u64,
-    pub oid: u64,
-    pub cloid: Option<String>,
+    pub order: Option<OrderStatusInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderStatusInfo {
+    pub order: OpenOrder,
+    /// e.g. `open`, `filled`, `canceled`, `triggered` or `rejected`
+    pub status: String,
+    pub status_timestamp: u64,
 }
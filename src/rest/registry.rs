@@ -0,0 +1,264 @@
+use crate::Decimal;
+use crate::error::{Error, Result};
+use crate::execution::models::{HyperliquidOrderRequest, HyperliquidOrderType};
+use crate::execution::precision::{MarketKind, PriceSizeRules};
+use crate::rest::info::HyperliquidInfoClient;
+use crate::rest::models::{SpotMetaTokenUniverse, Universe};
+use std::collections::HashMap;
+
+/// Spot pairs are addressed by `SPOT_ASSET_OFFSET + pair index` in exchange actions
+pub const SPOT_ASSET_OFFSET: u32 = 10000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetInfo {
+    /// Coin name for perps, pair name (e.g. `PURR/USDC` or `@107`) for spot
+    pub name: String,
+    /// Asset id used in exchange actions
+    pub asset: u32,
+    pub kind: MarketKind,
+    /// `szDecimals` of the perp, or of the base token for spot pairs
+    pub sz_decimals: u32,
+}
+
+impl AssetInfo {
+    pub fn rules(&self) -> PriceSizeRules {
+        PriceSizeRules {
+            kind: self.kind,
+            sz_decimals: self.sz_decimals,
+        }
+    }
+}
+
+/// Resolves coin and spot pair names to the asset ids used by exchange actions.
+///
+/// Non-canonical spot pairs are named `@<index>` by the exchange; they can also be looked up
+/// as `BASE/QUOTE`.
+#[derive(Debug, Clone, Default)]
+pub struct AssetRegistry {
+    assets: HashMap<u32, AssetInfo>,
+    names: HashMap<String, u32>,
+}
+
+impl AssetRegistry {
+    pub fn from_metadata(perps: &Universe, spot: &SpotMetaTokenUniverse) -> Self {
+        let mut this = Self::default();
+        this.update(perps, spot);
+        this
+    }
+
+    /// Fetch perp and spot metadata and build a registry from it
+    pub async fn load(info: &HyperliquidInfoClient) -> Result<Self> {
+        let mut this = Self::default();
+        this.refresh(info).await?;
+        Ok(this)
+    }
+
+    /// Re-fetch metadata to pick up newly listed assets
+    pub async fn refresh(&mut self, info: &HyperliquidInfoClient) -> Result<()> {
+        let (perps, spot) = tokio::try_join!(info.metadata(), info.spot_metadata())?;
+        self.update(&perps, &spot);
+        Ok(())
+    }
+
+    /// Replace the registry contents with the given metadata
+    pub fn update(&mut self, perps: &Universe, spot: &SpotMetaTokenUniverse) {
+        self.assets.clear();
+        self.names.clear();
+        for (index, asset) in perps.universe.iter().enumerate() {
+            self.insert(AssetInfo {
+                name: asset.name.clone(),
+                asset: index as u32,
+                kind: MarketKind::Perp,
+                sz_decimals: asset.szDecimals,
+            });
+        }
+
+        let tokens: HashMap<u32, _> = spot.tokens.iter().map(|t| (t.index, t)).collect();
+        let mut aliases = vec![];
+        for pair in &spot.universe {
+            let [base, quote] = pair.tokens;
+            let Some(base) = tokens.get(&base) else {
+                continue;
+            };
+            let asset = pair.asset();
+            self.insert(AssetInfo {
+                name: pair.name.clone(),
+                asset,
+                kind: MarketKind::Spot,
+                sz_decimals: base.szDecimals,
+            });
+            if let Some(quote) = tokens.get(&quote) {
+                aliases.push((format!("{}/{}", base.name, quote.name), asset));
+            }
+        }
+        // real pair names take precedence over aliases
+        for (alias, asset) in aliases {
+            self.names.entry(alias).or_insert(asset);
+        }
+    }
+
+    fn insert(&mut self, info: AssetInfo) {
+        self.names.insert(info.name.clone(), info.asset);
+        self.assets.insert(info.asset, info);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AssetInfo> {
+        self.assets.get(self.names.get(name)?)
+    }
+    pub fn get_by_asset(&self, asset: u32) -> Option<&AssetInfo> {
+        self.assets.get(&asset)
+    }
+    pub fn asset(&self, name: &str) -> Option<u32> {
+        self.names.get(name).copied()
+    }
+    pub fn name(&self, asset: u32) -> Option<&str> {
+        self.assets.get(&asset).map(|info| info.name.as_str())
+    }
+    pub fn sz_decimals(&self, name: &str) -> Option<u32> {
+        self.get(name).map(|info| info.sz_decimals)
+    }
+
+    /// Like [`Self::asset`], but fails with [`Error::UnknownAsset`]
+    pub fn resolve(&self, name: &str) -> Result<u32> {
+        self.asset(name)
+            .ok_or_else(|| Error::UnknownAsset(name.to_string()))
+    }
+
+    /// Build an order on a perp coin or spot pair, checked against the asset's tick and lot
+    /// rules. Spot pairs get their `10000 + index` asset id
+    pub fn order_request(
+        &self,
+        name: &str,
+        is_buy: bool,
+        limit_px: Decimal,
+        sz: Decimal,
+        order_type: HyperliquidOrderType,
+    ) -> Result<HyperliquidOrderRequest> {
+        let info = self
+            .get(name)
+            .ok_or_else(|| Error::UnknownAsset(name.to_string()))?;
+        let order = HyperliquidOrderRequest {
+            asset: info.asset,
+            is_buy,
+            limit_px,
+            sz,
+            reduce_only: false,
+            order_type,
+            cloid: None,
+        };
+        info.rules().validate_order(&order)?;
+        Ok(order)
+    }
+
+    pub fn len(&self) -> usize {
+        self.assets.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.assets.is_empty()
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &AssetInfo> {
+        self.assets.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::models::HyperliquidTif;
+
+    fn d(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    fn perps(names: &[(&str, u32)]) -> Universe {
+        let universe = names
+            .iter()
+            .map(|(name, sz_decimals)| serde_json::json!({"name": name, "szDecimals": sz_decimals}))
+            .collect::<Vec<_>>();
+        serde_json::from_value(serde_json::json!({ "universe": universe })).unwrap()
+    }
+
+    fn spot() -> SpotMetaTokenUniverse {
+        let token = |name: &str, index: u32, sz_decimals: u32| {
+            serde_json::json!({
+                "name": name,
+                "szDecimals": sz_decimals,
+                "weiDecimals": 8,
+                "index": index,
+                "tokenId": "0x6d1e7cde53ba9467b783cb7c530ce054",
+                "isCanonical": true,
+                "evmContract": null,
+                "fullName": null,
+                "deployerTradingFeeShare": "0.0"
+            })
+        };
+        serde_json::from_value(serde_json::json!({
+            "tokens": [token("USDC", 0, 8), token("PURR", 1, 0), token("HYPE", 150, 2)],
+            "universe": [
+                {"name": "PURR/USDC", "tokens": [1, 0], "index": 0, "isCanonical": true},
+                {"name": "@107", "tokens": [150, 0], "index": 107, "isCanonical": false}
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_perp_and_spot() {
+        let registry = AssetRegistry::from_metadata(&perps(&[("BTC", 5), ("ETH", 4)]), &spot());
+        assert_eq!(registry.len(), 4);
+
+        assert_eq!(registry.asset("BTC"), Some(0));
+        assert_eq!(registry.asset("ETH"), Some(1));
+        assert_eq!(registry.sz_decimals("ETH"), Some(4));
+        assert_eq!(
+            registry.get("ETH").unwrap().rules(),
+            PriceSizeRules::perp(4)
+        );
+
+        assert_eq!(registry.asset("PURR/USDC"), Some(10000));
+        assert_eq!(registry.asset("@107"), Some(10107));
+        assert_eq!(registry.asset("HYPE/USDC"), Some(10107));
+        assert_eq!(registry.name(10107), Some("@107"));
+        let hype = registry.get_by_asset(10107).unwrap();
+        assert_eq!(hype.kind, MarketKind::Spot);
+        assert_eq!(hype.rules(), PriceSizeRules::spot(2));
+
+        assert!(matches!(
+            registry.resolve("DOGE"),
+            Err(Error::UnknownAsset(name)) if name == "DOGE"
+        ));
+        assert_eq!(registry.name(3), None);
+    }
+
+    #[test]
+    fn test_spot_order_request() {
+        let registry = AssetRegistry::from_metadata(&perps(&[("BTC", 5)]), &spot());
+        let gtc = || HyperliquidOrderType::Limit {
+            tif: HyperliquidTif::Gtc,
+        };
+        let order = registry
+            .order_request("HYPE/USDC", true, d("21.5"), d("3.25"), gtc())
+            .unwrap();
+        assert_eq!(order.asset, 10107);
+        let order = registry
+            .order_request("PURR/USDC", false, d("0.1234"), d("100"), gtc())
+            .unwrap();
+        assert_eq!(order.asset, 10000);
+        // PURR has no size decimals
+        let result = registry.order_request("PURR/USDC", false, d("0.1234"), d("100.5"), gtc());
+        assert!(matches!(result, Err(Error::InvalidOrder(_))), "{result:?}");
+        let result = registry.order_request("@1", false, d("1"), d("1"), gtc());
+        assert!(matches!(result, Err(Error::UnknownAsset(_))), "{result:?}");
+    }
+
+    #[test]
+    fn test_update_picks_up_new_listings() {
+        let mut registry = AssetRegistry::from_metadata(&perps(&[("BTC", 5)]), &spot());
+        assert_eq!(registry.asset("SOL"), None);
+
+        registry.update(&perps(&[("BTC", 5), ("ETH", 4), ("SOL", 2)]), &spot());
+        assert_eq!(registry.asset("SOL"), Some(2));
+        assert_eq!(registry.name(2), Some("SOL"));
+        assert_eq!(registry.len(), 5);
+    }
+}
@@ -1,12 +1,12 @@
 use crate::execution::models::HyperliquidChain;
 use crate::execution::models::agent::agent_sol;
 use alloy::dyn_abi::Eip712Domain;
-use alloy::primitives::{B256, PrimitiveSignature, U256, address};
+use alloy::primitives::{Address, B256, PrimitiveSignature, U256, address, keccak256};
 use alloy::signers::Signer;
 use alloy::signers::local::PrivateKeySigner;
 use alloy::sol_types::eip712_domain;
 use serde::Serialize;
-use std::fmt::{Debug, Formatter};
+use std::fmt::{Debug, Display, Formatter};
 
 pub const MAINNET_DOMAIN: &Eip712Domain = &eip712_domain! {
     name: "Exchange",
@@ -27,6 +27,16 @@ pub const L1_DOMAIN: &Eip712Domain = &eip712_domain! {
     chain_id: 1337,
     verifying_contract: address!("0x0000000000000000000000000000000000000000") ,
 };
+/// Domain of user-signed actions, whose chain id is the action's `signatureChainId`
+pub fn user_signed_domain(signature_chain_id: u64) -> Eip712Domain {
+    eip712_domain! {
+        name: "HyperliquidSignTransaction",
+        version: "1",
+        chain_id: signature_chain_id,
+        verifying_contract: Address::ZERO,
+    }
+}
+
 pub const fn get_domain(chain: HyperliquidChain) -> &'static Eip712Domain {
     match chain {
         HyperliquidChain::Arbitrum => MAINNET_DOMAIN,
@@ -43,17 +53,18 @@ pub struct HyperliquidSignature {
 impl Debug for HyperliquidSignature {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("HyperliquidSignature")
-            .field("r", &hex::encode(&self.r.to_be_bytes::<32>()))
-            .field("s", &hex::encode(&self.s.to_be_bytes::<32>()))
+            .field("r", &hex::encode(self.r.to_be_bytes::<32>()))
+            .field("s", &hex::encode(self.s.to_be_bytes::<32>()))
             .field("v", &self.v)
             .finish()
     }
 }
-#[allow(dead_code)]
-impl HyperliquidSignature {
-    pub fn to_string(&self) -> String {
-        hex::encode(self.as_primitive_signature().as_bytes())
+impl Display for HyperliquidSignature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&hex::encode(self.as_primitive_signature().as_bytes()))
     }
+}
+impl HyperliquidSignature {
     pub fn as_primitive_signature(&self) -> PrimitiveSignature {
         PrimitiveSignature::new(self.r, self.s, self.v != 27)
     }
@@ -103,15 +114,175 @@ pub async fn sign_l1_action_inner(
     Ok(sig.into())
 }
 
+/// An action signed directly by the user's wallet with EIP-712 typed data, as opposed to the
+/// agent signature over the msgpack hash used by L1 actions.
+pub trait UserSignedAction {
+    /// Encoded EIP-712 type, e.g.
+    /// `HyperliquidTransaction:UsdSend(string hyperliquidChain,string destination,...)`
+    const EIP712_TYPE: &'static str;
+    fn signature_chain_id(&self) -> u64;
+    /// Nonce of the request carrying this action
+    fn nonce(&self) -> u64;
+    /// Encoded field values, in the order of [`Self::EIP712_TYPE`]
+    fn eip712_data_words(&self) -> Vec<B256>;
+
+    fn eip712_hash_struct(&self) -> B256 {
+        let mut data = keccak256(Self::EIP712_TYPE).to_vec();
+        for word in self.eip712_data_words() {
+            data.extend_from_slice(&word[..]);
+        }
+        keccak256(data)
+    }
+    fn eip712_signing_hash(&self) -> B256 {
+        let mut digest_input = [0u8; 2 + 32 + 32];
+        digest_input[0] = 0x19;
+        digest_input[1] = 0x01;
+        digest_input[2..34]
+            .copy_from_slice(&user_signed_domain(self.signature_chain_id()).hash_struct()[..]);
+        digest_input[34..66].copy_from_slice(&self.eip712_hash_struct()[..]);
+        keccak256(digest_input)
+    }
+}
+
+/// Sign a user-signed action with the user's wallet
+pub async fn sign_user_signed_action(
+    wallet: &PrivateKeySigner,
+    action: &impl UserSignedAction,
+) -> crate::error::Result<HyperliquidSignature> {
+    let sig = wallet.sign_hash(&action.eip712_signing_hash()).await?;
+    Ok(sig.into())
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
+    use super::*;
+    use crate::execution::models::{
+        Action, ApproveAgent, SpotSend, UsdClassTransfer, UsdSend, Withdraw3,
+    };
     use alloy::primitives::keccak256;
     use alloy::signers::local::PrivateKeySigner;
+    use malachite::base::strings::ToLowerHexString;
 
     const PRIVATE_KEY: &str = "e908f86dbb4d55ac876378565aafeabc187f6690f046459397b17d9b9a19688e";
     pub fn get_wallet() -> PrivateKeySigner {
         PRIVATE_KEY.parse::<PrivateKeySigner>().unwrap()
     }
+    /// Key used by the signing tests of the official Python SDK
+    pub(crate) fn reference_wallet() -> PrivateKeySigner {
+        "0123456789012345678901234567890123456789012345678901234567890123"
+            .parse()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sign_usd_send_and_withdraw() -> eyre::Result<()> {
+        // vectors from the official python SDK
+        let destination = "0x5e9ee1089755c3435139848e47e6635505d5a13a".parse()?;
+        let action = UsdSend::new(
+            HyperliquidChain::ArbitrumGoerli,
+            destination,
+            "1".parse()?,
+            1687816341423,
+        );
+        assert_eq!(
+            serde_json::to_string(&Action::UsdSend(action.clone()))?,
+            r#"{"type":"usdSend","signatureChainId":"0x66eee","hyperliquidChain":"Testnet","destination":"0x5e9ee1089755c3435139848e47e6635505d5a13a","amount":"1","time":1687816341423}"#
+        );
+        let sig = sign_user_signed_action(&reference_wallet(), &action).await?;
+        assert_eq!(
+            sig.r.to_lower_hex_string(),
+            "637b37dd731507cdd24f46532ca8ba6eec616952c56218baeff04144e4a77073"
+        );
+        assert_eq!(
+            sig.s.to_lower_hex_string(),
+            "11a6a24900e6e314136d2592e2f8d502cd89b7c15b198e1bee043c9589f9fad7"
+        );
+        assert_eq!(sig.v, 27);
+
+        let action = Withdraw3::new(
+            HyperliquidChain::ArbitrumGoerli,
+            destination,
+            "1".parse()?,
+            1687816341423,
+        );
+        assert_eq!(
+            serde_json::to_string(&Action::Withdraw3(action.clone()))?,
+            r#"{"type":"withdraw3","signatureChainId":"0x66eee","hyperliquidChain":"Testnet","destination":"0x5e9ee1089755c3435139848e47e6635505d5a13a","amount":"1","time":1687816341423}"#
+        );
+        let sig = sign_user_signed_action(&reference_wallet(), &action).await?;
+        assert_eq!(
+            sig.r.to_lower_hex_string(),
+            "8363524c799e90ce9bc41022f7c39b4e9bdba786e5f9c72b20e43e1462c37cf9"
+        );
+        assert_eq!(
+            sig.s.to_lower_hex_string(),
+            "58b1411a775938b83e29182e8ef74975f9054c8e97ebf5ec2dc8d51bfc893881"
+        );
+        assert_eq!(sig.v, 28);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sign_spot_send_class_transfer_and_approve_agent() -> eyre::Result<()> {
+        let wallet = reference_wallet();
+        let destination = "0x5e9ee1089755c3435139848e47e6635505d5a13a".parse()?;
+
+        let spot_send = SpotSend::new(
+            HyperliquidChain::ArbitrumGoerli,
+            destination,
+            "PURR:0xc4bf3f870c0e9465323c0b6ed28096c2",
+            "10.5".parse()?,
+            1687816341423,
+        );
+        assert_eq!(
+            serde_json::to_string(&Action::SpotSend(spot_send.clone()))?,
+            r#"{"type":"spotSend","signatureChainId":"0x66eee","hyperliquidChain":"Testnet","destination":"0x5e9ee1089755c3435139848e47e6635505d5a13a","token":"PURR:0xc4bf3f870c0e9465323c0b6ed28096c2","amount":"10.5","time":1687816341423}"#
+        );
+        let class_transfer = UsdClassTransfer::new(
+            HyperliquidChain::ArbitrumGoerli,
+            "100".parse()?,
+            true,
+            1687816341423,
+        );
+        assert_eq!(
+            serde_json::to_string(&Action::UsdClassTransfer(class_transfer.clone()))?,
+            r#"{"type":"usdClassTransfer","signatureChainId":"0x66eee","hyperliquidChain":"Testnet","amount":"100","toPerp":true,"nonce":1687816341423}"#
+        );
+        let approve_agent = ApproveAgent::new(
+            HyperliquidChain::ArbitrumGoerli,
+            destination,
+            None,
+            1687816341423,
+        );
+        assert_eq!(
+            serde_json::to_string(&approve_agent)?,
+            r#"{"signatureChainId":"0x66eee","hyperliquidChain":"Testnet","agentAddress":"0x5e9ee1089755c3435139848e47e6635505d5a13a","nonce":1687816341423}"#
+        );
+
+        // regression snapshots of this crate's output, not vectors from the official SDKs
+        let hashes = [
+            spot_send.eip712_signing_hash(),
+            class_transfer.eip712_signing_hash(),
+            approve_agent.eip712_signing_hash(),
+        ];
+        assert_eq!(
+            hashes.map(|hash| hash.to_string()),
+            [
+                "0xc997dd3f594754d0d1a15f74a7e38cfd0391b0225ee43fb7dc07bb8a53798ea3",
+                "0xeaf20f38ac5b8dcc47c922c5d0673902156db1bc3e853a5a6937b73d5c551cd8",
+                "0xac29ba7461823bad6b5e24ec98c2089135e5aa3ec14e7e5a8e50e9bbf965cd95"
+            ]
+        );
+        for hash in hashes {
+            let sig: HyperliquidSignature = wallet.sign_hash(&hash).await?.into();
+            let signer = sig
+                .as_primitive_signature()
+                .recover_address_from_prehash(&hash)?;
+            assert_eq!(signer, wallet.address());
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_keccak256() {
         let data = "1234";
@@ -1,20 +1,17 @@
 #[allow(unused_imports)]
 use crate::HYPERLIQUID;
 
+use crate::error::Result;
+use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
-pub(crate) fn trim_float_in_string_for_hashing(x: &mut String) -> &str {
-    if x.contains('.') {
-        while x.ends_with('0') {
-            x.pop();
-        }
-        if x.ends_with('.') {
-            x.pop();
-        }
-    }
-    x.as_str()
-}
-
+#[allow(dead_code)]
 pub(crate) fn uuid_to_hex_string(uuid: Uuid) -> String {
     format!("0x{}", uuid.simple())
 }
+
+/// Milliseconds since the unix epoch, as used for exchange nonces
+pub(crate) fn get_timestamp_ms() -> Result<u64> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+    Ok(now.as_millis() as u64)
+}